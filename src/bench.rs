@@ -1,7 +1,9 @@
 use crate::position::START_POS_FEN;
 use crate::search::{
+    params::SearchParams,
     report::{Report, Reporter},
     search,
+    stats::SearchStats,
     stopper::Stopper,
     tt::{self, TranspositionTable},
 };
@@ -70,12 +72,14 @@ const BENCH_FENS: &[&str] = &[
 pub fn run(cli_args: &[String]) {
     let mut depth = BENCH_DEFAULT_DEPTH;
     let mut tt_mb = BENCH_DEFAULT_TT_MB;
+    let mut show_stats = false;
     let mut iter = cli_args.iter();
 
     while let Some(arg) = iter.next() {
         match arg.as_str() {
             "--depth" => depth = iter.next().unwrap().parse().unwrap(),
             "--tt-mb" => tt_mb = iter.next().unwrap().parse().unwrap(),
+            "--stats" => show_stats = true,
             _ => {}
         }
     }
@@ -94,7 +98,14 @@ pub fn run(cli_args: &[String]) {
     let mut stopper = Stopper::new();
     stopper.at_depth(Some(depth));
 
+    let params = SearchParams {
+        stats: show_stats,
+        ..Default::default()
+    };
+
     let mut total_nodes = 0;
+    let mut total_stats = SearchStats::default();
+    let mut max_seldepth = 0;
     let total_fens = BENCH_FENS.len();
     let max_fen_len = BENCH_FENS.iter().map(|fen| fen.len()).max().unwrap();
     let bench_started_at = Instant::now();
@@ -119,7 +130,7 @@ pub fn run(cli_args: &[String]) {
             writeln!(out, "{running_line}").unwrap();
         }
 
-        search(&mut pos, &mut tt, &reporter, &stopper);
+        search(&mut pos, &mut tt, &reporter, &stopper, &params);
 
         let elapsed = search_started_at.elapsed();
         let nodes = reporter.nodes();
@@ -138,6 +149,8 @@ pub fn run(cli_args: &[String]) {
         }
 
         total_nodes += nodes;
+        total_stats += reporter.stats();
+        max_seldepth = max_seldepth.max(reporter.seldepth());
 
         tt.clear();
     }
@@ -147,24 +160,50 @@ pub fn run(cli_args: &[String]) {
     writeln!(out, "\ntime: {c1}{:10.2?}{c2}", elapsed).unwrap();
     writeln!(out, "nodes: {c1}{total_nodes}{c2}").unwrap();
     writeln!(out, "nps: {c1}{}{c2}", total_nodes * 1000 / elapsed.as_millis().max(1)).unwrap();
+
+    if show_stats {
+        writeln!(out).unwrap();
+
+        for line in total_stats.summary(total_nodes as u64) {
+            writeln!(out, "{line}").unwrap();
+        }
+
+        writeln!(out, "seldepth {max_seldepth}").unwrap();
+    }
 }
 
 struct BenchReporter {
     nodes: Cell<u128>,
+    seldepth: Cell<u16>,
+    stats: Cell<SearchStats>,
 }
 
 impl BenchReporter {
     pub fn new() -> Self {
-        Self { nodes: Cell::new(0) }
+        Self {
+            nodes: Cell::new(0),
+            seldepth: Cell::new(0),
+            stats: Cell::new(SearchStats::default()),
+        }
     }
 
     pub fn nodes(&self) -> u128 {
         self.nodes.get()
     }
+
+    pub fn seldepth(&self) -> u16 {
+        self.seldepth.get()
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        self.stats.get()
+    }
 }
 
 impl Reporter for BenchReporter {
     fn send(&self, report: &Report) {
         self.nodes.set(report.nodes);
+        self.seldepth.set(report.seldepth);
+        self.stats.set(report.stats);
     }
 }
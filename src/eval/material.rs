@@ -0,0 +1,97 @@
+use super::Evaluator;
+use super::terms::PIECE_WEIGHTS;
+use crate::colour::Colour;
+use crate::movegen::Move;
+use crate::piece::Piece;
+use crate::position::{Board, Position};
+
+// Counts material only, kept up to date incrementally through the move hooks.
+// Mostly useful for testing the search in isolation from the handcrafted eval.
+pub struct MaterialEval {
+    material: [i32; 2],
+}
+
+impl MaterialEval {
+    pub fn new(pos: &Position) -> Self {
+        Self {
+            material: [
+                count_material(Colour::White, &pos.board),
+                count_material(Colour::Black, &pos.board),
+            ],
+        }
+    }
+
+    fn apply(&mut self, mv: &Move, sign: i32) {
+        let colour = mv.piece.colour();
+
+        if let Some(captured_piece) = mv.captured_piece {
+            self.material[colour.flip()] -= sign * PIECE_WEIGHTS[captured_piece];
+        }
+
+        if let Some(promotion_piece) = mv.promotion_piece {
+            self.material[colour] += sign * (PIECE_WEIGHTS[promotion_piece] - PIECE_WEIGHTS[mv.piece]);
+        }
+    }
+}
+
+impl Evaluator for MaterialEval {
+    fn eval(&mut self, pos: &Position) -> i32 {
+        debug_assert_eq!(self.material, Self::new(pos).material);
+
+        let colour = pos.colour_to_move;
+        self.material[colour] - self.material[colour.flip()]
+    }
+
+    fn do_move(&mut self, mv: &Move) {
+        self.apply(mv, 1);
+    }
+
+    fn undo_move(&mut self, mv: &Move) {
+        self.apply(mv, -1);
+    }
+}
+
+fn count_material(colour: Colour, board: &Board) -> i32 {
+    Piece::pieces_by_colour(colour)
+        .iter()
+        .map(|&piece| PIECE_WEIGHTS[piece] * board.count_pieces(piece) as i32)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::Colour;
+    use crate::square::Square;
+    use crate::testing::*;
+
+    #[test]
+    fn eval_is_from_the_side_to_move() {
+        let white_to_move = parse_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1");
+        let black_to_move = parse_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1");
+
+        assert_eq!(MaterialEval::new(&white_to_move).eval(&white_to_move), 900);
+        assert_eq!(MaterialEval::new(&black_to_move).eval(&black_to_move), -900);
+    }
+
+    #[test]
+    fn captures_and_promotions_are_tracked_incrementally() {
+        let mut pos = parse_fen("3nk3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+        let mut evaluator = MaterialEval::new(&pos);
+        let mv = Move {
+            captured_piece: Some(Piece::BN),
+            promotion_piece: Some(Piece::WQ),
+            ..make_promotion_move(Colour::White, Square::C7, Square::D8, Piece::WQ)
+        };
+
+        evaluator.do_move(&mv);
+        pos.do_move(&mv);
+
+        assert_eq!(evaluator.eval(&pos), -900);
+
+        evaluator.undo_move(&mv);
+        pos.undo_move(&mv);
+
+        assert_eq!(evaluator.eval(&pos), 100 - 300);
+    }
+}
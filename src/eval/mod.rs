@@ -1,23 +1,49 @@
 use crate::colour::Colour;
+use crate::movegen::Move;
 use crate::position::Position;
-use crate::search::MAX_DEPTH;
-
-mod phase;
+use crate::search::MAX_PLY;
 
+pub mod material;
+pub mod phase;
 pub mod terms;
+pub mod trace;
+pub mod wdl;
 
 use phase::phase_eval;
-use terms::{EvalTerm, TERMS};
+use terms::{Attacks, EvalTerm, TERMS};
 
 pub const EVAL_MAX: i32 = 10_000;
 pub const EVAL_MIN: i32 = -EVAL_MAX;
 pub const EVAL_DRAW: i32 = 0;
 pub const EVAL_MATE: i32 = EVAL_MAX;
-pub const EVAL_MATE_THRESHOLD: i32 = EVAL_MATE - MAX_DEPTH as i32;
+pub const EVAL_MATE_THRESHOLD: i32 = EVAL_MATE - MAX_PLY as i32;
+
+// An evaluation function that the search can be run with. Evals are from the
+// point of view of the side to move. Evaluators that keep incremental state
+// can use the move hooks, which the search calls just before each move is
+// made or undone on the position (null moves aside, since they don't change
+// the pieces on the board).
+pub trait Evaluator {
+    fn eval(&mut self, pos: &Position) -> i32;
+
+    fn do_move(&mut self, _mv: &Move) {}
+
+    fn undo_move(&mut self, _mv: &Move) {}
+}
+
+pub struct HandcraftedEval;
+
+impl Evaluator for HandcraftedEval {
+    #[inline(always)]
+    fn eval(&mut self, pos: &Position) -> i32 {
+        eval(pos)
+    }
+}
 
 pub fn eval(pos: &Position) -> i32 {
-    let eval = TERMS.iter().fold(EvalTerm::zero(), |acc, term| {
-        acc + term(Colour::White, &pos.board) - term(Colour::Black, &pos.board)
+    let attacks = Attacks::new(&pos.board);
+    let eval = TERMS.iter().fold(EvalTerm::zero(), |acc, (_, term)| {
+        acc + term(Colour::White, pos, &attacks) - term(Colour::Black, pos, &attacks)
     });
 
     let phased_eval = phase_eval(eval, &pos.board);
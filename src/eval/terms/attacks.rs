@@ -0,0 +1,154 @@
+use super::pawn_attacks;
+use crate::colour::Colour;
+use crate::movegen::{get_attacks, get_slider_attacks};
+use crate::piece::Piece;
+use crate::position::Board;
+use crate::square::Square;
+
+// The squares attacked by every piece on the board, worked out once per eval
+// and shared between the terms that need them. For mobility, sliding pieces
+// also see through friendly pieces that move along the same lines, so a
+// bishop x-rays through its own queen and a rook through its own rooks and
+// queen. These x-rays aren't real attacks, so they're kept apart from the
+// attacks used for checks, threats and defence.
+pub struct Attacks {
+    squares: [u64; 64],
+    x_rays: [u64; 64],
+    pieces: [u64; 12],
+    colours: [u64; 2],
+}
+
+impl Attacks {
+    pub fn new(board: &Board) -> Self {
+        let mut attacks = Self {
+            squares: [0; 64],
+            x_rays: [0; 64],
+            pieces: [0; 12],
+            colours: [0; 2],
+        };
+
+        for &piece in Piece::pieces() {
+            let mut pieces = board.pieces(piece);
+
+            while pieces != 0 {
+                let square = Square::next(&mut pieces);
+                let squares = piece_attacks(piece, square, board);
+
+                attacks.squares[square] = squares;
+                attacks.x_rays[square] = x_ray_attacks(piece, square, board).unwrap_or(squares);
+                attacks.pieces[piece] |= squares;
+                attacks.colours[piece.colour()] |= squares;
+            }
+        }
+
+        attacks
+    }
+
+    // The squares attacked by the piece on the given square, if there is one.
+    pub fn by_square(&self, square: Square) -> u64 {
+        self.squares[square]
+    }
+
+    // As above, but with sliders seeing through friendly sliders.
+    pub fn x_rays_by_square(&self, square: Square) -> u64 {
+        self.x_rays[square]
+    }
+
+    pub fn by_piece(&self, piece: Piece) -> u64 {
+        self.pieces[piece]
+    }
+
+    pub fn by_colour(&self, colour: Colour) -> u64 {
+        self.colours[colour]
+    }
+}
+
+fn piece_attacks(piece: Piece, square: Square, board: &Board) -> u64 {
+    match piece {
+        Piece::WP | Piece::BP => pawn_attacks(piece.colour(), square.u64()),
+        _ => get_attacks(piece, square, board),
+    }
+}
+
+// Only bishops and rooks see through anything.
+fn x_ray_attacks(piece: Piece, square: Square, board: &Board) -> Option<u64> {
+    let colour = piece.colour();
+    let queens = board.pieces(Piece::queen(colour));
+
+    match piece {
+        Piece::WB | Piece::BB => Some(get_slider_attacks(piece, square, board.occupancy() & !queens)),
+        Piece::WR | Piece::BR => {
+            let rooks = board.pieces(Piece::rook(colour));
+            Some(get_slider_attacks(piece, square, board.occupancy() & !(queens | rooks)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn pawns_attack_empty_squares() {
+        let pos = parse_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        let attacks = Attacks::new(&pos.board);
+
+        assert_eq!(attacks.by_square(Square::E2), Square::D3.u64() | Square::F3.u64());
+    }
+
+    #[test]
+    fn attacks_are_combined_by_piece_and_colour() {
+        let pos = parse_fen("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1");
+        let attacks = Attacks::new(&pos.board);
+
+        assert_eq!(
+            attacks.by_piece(Piece::WN),
+            attacks.by_square(Square::B1) | attacks.by_square(Square::G1)
+        );
+        assert_eq!(
+            attacks.by_colour(Colour::White),
+            attacks.by_piece(Piece::WN) | attacks.by_piece(Piece::WK)
+        );
+        assert_eq!(attacks.by_colour(Colour::Black), attacks.by_piece(Piece::BK));
+    }
+
+    #[test]
+    fn bishops_x_ray_through_their_own_queen() {
+        let pos = parse_fen("4k3/8/8/8/8/8/1Q6/B3K3 w - - 0 1");
+        let attacks = Attacks::new(&pos.board);
+
+        assert_ne!(attacks.x_rays_by_square(Square::A1) & Square::H8.u64(), 0);
+        assert_eq!(attacks.by_square(Square::A1) & Square::H8.u64(), 0);
+    }
+
+    #[test]
+    fn rooks_x_ray_through_their_own_rooks_and_queen() {
+        let pos = parse_fen("4k3/8/8/8/8/Q7/R7/R3K3 w - - 0 1");
+        let attacks = Attacks::new(&pos.board);
+
+        assert_ne!(attacks.x_rays_by_square(Square::A1) & Square::A8.u64(), 0);
+    }
+
+    #[test]
+    fn x_rays_are_not_real_attacks() {
+        let pos = parse_fen("4k3/8/8/8/8/Q7/R7/R3K3 w - - 0 1");
+        let attacks = Attacks::new(&pos.board);
+
+        // The king on e1 is attacked, the squares beyond the rook on a2 aren't.
+        assert_eq!(
+            attacks.by_square(Square::A1),
+            Square::A2.u64() | Square::B1.u64() | Square::C1.u64() | Square::D1.u64() | Square::E1.u64()
+        );
+        assert_eq!(attacks.by_piece(Piece::WR) & Square::A8.u64(), 0);
+    }
+
+    #[test]
+    fn sliders_do_not_x_ray_through_other_pieces() {
+        let pos = parse_fen("4k3/8/8/8/8/N7/8/R3K3 w - - 0 1");
+        let attacks = Attacks::new(&pos.board);
+
+        assert_eq!(attacks.x_rays_by_square(Square::A1) & Square::A8.u64(), 0);
+    }
+}
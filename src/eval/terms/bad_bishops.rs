@@ -0,0 +1,57 @@
+use super::{Attacks, EvalTerm};
+use crate::colour::Colour;
+use crate::piece::Piece;
+use crate::position::Position;
+use crate::square::Square;
+
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+const PAWN_ON_BISHOP_COLOUR_MG: i32 = -2;
+const PAWN_ON_BISHOP_COLOUR_EG: i32 = -5;
+
+// A bishop is hemmed in by its own pawns when they're fixed on squares of its
+// colour, and a blocked pawn can't move out of the way so it counts twice.
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    let (mut mg, mut eg) = (0, 0);
+    let board = &pos.board;
+    let pawns = board.pieces(Piece::pawn(colour));
+    let blocked_pawns = match colour {
+        Colour::White => pawns & (board.occupancy() >> 8),
+        _ => pawns & (board.occupancy() << 8),
+    };
+    let mut bishops = board.pieces(Piece::bishop(colour));
+
+    while bishops != 0 {
+        let square = Square::next(&mut bishops);
+        let squares = if square.u64() & LIGHT_SQUARES != 0 { LIGHT_SQUARES } else { !LIGHT_SQUARES };
+        let count = ((pawns & squares).count_ones() + (blocked_pawns & squares).count_ones()) as i32;
+
+        mg += count * PAWN_ON_BISHOP_COLOUR_MG;
+        eg += count * PAWN_ON_BISHOP_COLOUR_EG;
+    }
+
+    EvalTerm::new(mg, eg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn bishops_hemmed_in_by_their_own_pawns_are_bad() {
+        let bad_bishop = parse_fen("4k3/8/8/8/3P4/4P3/8/2B1K3 w - - 0 1");
+        let good_bishop = parse_fen("4k3/8/8/8/4P3/3P4/8/2B1K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &bad_bishop).eg() < eval_term(eval, Colour::White, &good_bishop).eg());
+        assert_eq!(eval_term(eval, Colour::White, &good_bishop).eg(), 0);
+    }
+
+    #[test]
+    fn blocked_pawns_make_bishops_worse() {
+        let blocked = parse_fen("4k3/8/8/3p4/3P4/8/8/2B1K3 w - - 0 1");
+        let not_blocked = parse_fen("4k3/8/8/8/3P4/8/8/2B1K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &blocked).eg() < eval_term(eval, Colour::White, &not_blocked).eg());
+    }
+}
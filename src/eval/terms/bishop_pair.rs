@@ -0,0 +1,37 @@
+use super::{Attacks, EvalTerm};
+use crate::colour::Colour;
+use crate::piece::Piece;
+use crate::position::Position;
+
+const BISHOP_PAIR_MG: i32 = 25;
+const BISHOP_PAIR_EG: i32 = 45;
+
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    if pos.board.count_pieces(Piece::bishop(colour)) < 2 {
+        return EvalTerm::zero();
+    }
+
+    EvalTerm::new(BISHOP_PAIR_MG, BISHOP_PAIR_EG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn bishop_pair_is_good() {
+        let pos = parse_fen("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &pos).mg() > 0);
+        assert!(eval_term(eval, Colour::White, &pos).eg() > 0);
+    }
+
+    #[test]
+    fn a_single_bishop_is_not_a_pair() {
+        let pos = parse_fen("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::Black, &pos).mg(), 0);
+        assert_eq!(eval_term(eval, Colour::Black, &pos).eg(), 0);
+    }
+}
@@ -0,0 +1,45 @@
+use super::{Attacks, EvalTerm};
+use crate::colour::Colour;
+use crate::piece::Piece;
+use crate::position::Position;
+
+const HANGING_MG: i32 = 30;
+const HANGING_EG: i32 = 20;
+
+// Enemy pieces that we attack and they don't defend are likely to be lost.
+pub fn eval(colour: Colour, pos: &Position, attacks: &Attacks) -> EvalTerm {
+    let board = &pos.board;
+    let them = colour.flip();
+    let their_pieces = board.pieces_by_colour(them) & !board.pieces(Piece::king(them));
+    let hanging = their_pieces & attacks.by_colour(colour) & !attacks.by_colour(them);
+    let count = hanging.count_ones() as i32;
+
+    EvalTerm::new(count * HANGING_MG, count * HANGING_EG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn attacking_undefended_pieces_is_good() {
+        let pos = parse_fen("4k3/8/8/2n5/8/8/8/2R1K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &pos).mg() > 0);
+    }
+
+    #[test]
+    fn attacking_defended_pieces_is_not_hanging() {
+        let pos = parse_fen("4k3/1p6/2n5/8/8/8/8/2R1K3 w - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+    }
+
+    #[test]
+    fn attacking_the_king_is_not_hanging() {
+        let pos = parse_fen("4k3/8/8/8/8/8/8/4RK2 b - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+    }
+}
@@ -1,19 +1,45 @@
-use super::EvalTerm;
+use super::{Attacks, EvalTerm};
 use crate::colour::Colour;
+use crate::movegen::get_attacks;
 use crate::piece::Piece;
-use crate::position::Board;
-use crate::square::Square;
+use crate::position::{Board, Position};
+use crate::square::{FILES, Square};
 use lazy_static::lazy_static;
 
 const PAWN_SHIELD_CLOSE: i32 = 12;
 const PAWN_SHIELD_FAR: i32 = 6;
 
-pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
+// Attack units per king zone square attacked, indexed by piece type
+// (pawn, knight, bishop, rook, queen).
+const ZONE_ATTACK_UNITS: [i32; 5] = [0, 2, 2, 3, 5];
+const SAFE_CHECK_UNITS: [i32; 5] = [0, 6, 4, 7, 9];
+const HALF_OPEN_FILE_UNITS: i32 = 2;
+const OPEN_FILE_UNITS: i32 = 3;
+const MAX_ATTACK_UNITS: usize = 63;
+
+// Enemy pawns storming the king, indexed by how many ranks in front of it
+// they are. A pawn that's right in front is blocked by the king so isn't as
+// dangerous as one that's about to hit the pawn shield.
+const PAWN_STORM_MG: [i32; 5] = [0, 5, 25, 15, 5];
+
+const PAWN_PROXIMITY_EG: i32 = 6;
+
+pub fn eval(colour: Colour, pos: &Position, attacks: &Attacks) -> EvalTerm {
+    let board = &pos.board;
     let king_square = Square::first(board.pieces(Piece::king(colour)));
 
+    let mg = pawn_shield(colour, king_square, board)
+        - pawn_storm(colour, king_square, board)
+        - KING_DANGER[attack_units(colour, king_square, board, attacks)];
+    let eg = -pawn_proximity(king_square, board);
+
+    EvalTerm::new(mg, eg)
+}
+
+fn pawn_shield(colour: Colour, king_square: Square, board: &Board) -> i32 {
     // Evaluate pawn shields when the king is out of the centre.
     if (3..5).contains(&king_square.file()) {
-        return EvalTerm::zero();
+        return 0;
     }
 
     let (close, far) = PAWN_SHIELDS[colour][king_square];
@@ -21,12 +47,144 @@ pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
     let close_pawns = (pawns & close).count_ones() as i32;
     let far_pawns = (pawns & far).count_ones() as i32;
 
-    EvalTerm::new(close_pawns * PAWN_SHIELD_CLOSE + far_pawns * PAWN_SHIELD_FAR, 0)
+    close_pawns * PAWN_SHIELD_CLOSE + far_pawns * PAWN_SHIELD_FAR
+}
+
+fn pawn_storm(colour: Colour, king_square: Square, board: &Board) -> i32 {
+    let mut storm = 0;
+    let mut their_pawns = board.pieces(Piece::pawn(colour.flip())) & KING_FILES[king_square.file() as usize];
+
+    while their_pawns != 0 {
+        let square = Square::next(&mut their_pawns);
+
+        let ranks_in_front = match colour {
+            Colour::White => square.rank() as i32 - king_square.rank() as i32,
+            _ => king_square.rank() as i32 - square.rank() as i32,
+        };
+
+        if (1..PAWN_STORM_MG.len() as i32).contains(&ranks_in_front) {
+            storm += PAWN_STORM_MG[ranks_in_front as usize];
+        }
+    }
+
+    storm
+}
+
+// Count up the danger to the king from enemy pieces attacking the squares
+// around it, safe checks and open files nearby. The units are mapped through
+// a table that grows quadratically, since a lone attacker is rarely a threat
+// but each additional one makes a successful attack much more likely.
+fn attack_units(colour: Colour, king_square: Square, board: &Board, attacks: &Attacks) -> usize {
+    let them = colour.flip();
+    let zone = KING_ZONES[king_square];
+    let defended = [
+        Piece::pawn(colour),
+        Piece::knight(colour),
+        Piece::bishop(colour),
+        Piece::rook(colour),
+        Piece::queen(colour),
+        Piece::king(colour),
+    ]
+    .iter()
+    .fold(0, |defended, piece| defended | attacks.by_piece(*piece));
+    let their_occupancy = board.pieces_by_colour(them);
+
+    let mut units = 0;
+    let mut attackers = 0;
+
+    for piece in [
+        Piece::knight(them),
+        Piece::bishop(them),
+        Piece::rook(them),
+        Piece::queen(them),
+    ] {
+        let piece_type = piece as usize % 6;
+        let checks = get_attacks(piece, king_square, board);
+        let mut pieces = board.pieces(piece);
+
+        while pieces != 0 {
+            let piece_attacks = attacks.by_square(Square::next(&mut pieces));
+            let zone_attacks = (piece_attacks & zone).count_ones() as i32;
+
+            if zone_attacks > 0 {
+                attackers += 1;
+                units += zone_attacks * ZONE_ATTACK_UNITS[piece_type];
+            }
+
+            let safe_checks = piece_attacks & checks & !defended & !their_occupancy;
+
+            if safe_checks != 0 {
+                units += SAFE_CHECK_UNITS[piece_type];
+            }
+        }
+    }
+
+    // A single attacker can't mount much of an attack on its own.
+    if attackers < 2 {
+        return 0;
+    }
+
+    let our_pawns = board.pieces(Piece::pawn(colour));
+    let their_pawns = board.pieces(Piece::pawn(them));
+
+    for file in FILES
+        .iter()
+        .filter(|&&file| file & KING_FILES[king_square.file() as usize] != 0)
+    {
+        if (our_pawns | their_pawns) & file == 0 {
+            units += OPEN_FILE_UNITS;
+        } else if our_pawns & file == 0 {
+            units += HALF_OPEN_FILE_UNITS;
+        }
+    }
+
+    (units as usize).min(MAX_ATTACK_UNITS)
+}
+
+// In the endgame the king should head towards the pawns, whether to support
+// its own or to stop the opponent's.
+fn pawn_proximity(king_square: Square, board: &Board) -> i32 {
+    let mut pawns = board.pieces(Piece::WP) | board.pieces(Piece::BP);
+    let mut closest = 0;
+
+    while pawns != 0 {
+        let square = Square::next(&mut pawns);
+        let distance = king_square.distance(square) as i32;
+
+        if closest == 0 || distance < closest {
+            closest = distance;
+        }
+    }
+
+    closest * PAWN_PROXIMITY_EG
 }
 
 lazy_static! {
     static ref PAWN_SHIELDS: [[(u64, u64); 64]; 2] =
         [build_pawn_shields(Colour::White), build_pawn_shields(Colour::Black)];
+    static ref KING_ZONES: [u64; 64] = build_king_zones();
+    static ref KING_DANGER: [i32; MAX_ATTACK_UNITS + 1] = std::array::from_fn(|units| {
+        let units = units as i32;
+        (units * units / 4).min(400)
+    });
+}
+
+const KING_FILES: [u64; 8] = [
+    FILES[0] | FILES[1],
+    FILES[0] | FILES[1] | FILES[2],
+    FILES[1] | FILES[2] | FILES[3],
+    FILES[2] | FILES[3] | FILES[4],
+    FILES[3] | FILES[4] | FILES[5],
+    FILES[4] | FILES[5] | FILES[6],
+    FILES[5] | FILES[6] | FILES[7],
+    FILES[6] | FILES[7],
+];
+
+fn build_king_zones() -> [u64; 64] {
+    std::array::from_fn(|index| {
+        let square = Square::from_index(index as u8);
+        square.u64() | get_attacks(Piece::WK, square, &Board::empty())
+    })
 }
 
 fn build_pawn_shields(colour: Colour) -> [(u64, u64); 64] {
@@ -66,3 +224,95 @@ fn build_pawn_shields(colour: Colour) -> [(u64, u64); 64] {
 
     masks
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn pawn_shield_is_good() {
+        let shielded = parse_fen("6k1/8/8/8/8/8/5PPP/6K1 w - - 0 1");
+        let exposed = parse_fen("6k1/8/8/8/8/5PPP/8/6K1 w - - 0 1");
+
+        assert!(pawn_shield(Colour::White, Square::G1, &shielded.board) > 0);
+        assert!(
+            pawn_shield(Colour::White, Square::G1, &shielded.board)
+                > pawn_shield(Colour::White, Square::G1, &exposed.board)
+        );
+    }
+
+    #[test]
+    fn pieces_attacking_the_king_zone_are_dangerous() {
+        let attacked = parse_fen("6k1/5ppp/8/6q1/5n2/8/5PPP/6K1 w - - 0 1");
+        let not_attacked = parse_fen("6k1/5ppp/8/q7/n7/8/5PPP/6K1 w - - 0 1");
+
+        assert!(units(&attacked) > 0);
+        assert_eq!(units(&not_attacked), 0);
+        assert!(eval_term(eval, Colour::White, &attacked).mg() < eval_term(eval, Colour::White, &not_attacked).mg());
+    }
+
+    #[test]
+    fn a_lone_attacker_is_not_dangerous() {
+        let pos = parse_fen("6k1/5ppp/8/8/8/7q/5PPP/6K1 w - - 0 1");
+
+        assert_eq!(units(&pos), 0);
+    }
+
+    #[test]
+    fn king_danger_grows_faster_than_attack_units() {
+        assert!(KING_DANGER[20] > KING_DANGER[10] * 2);
+        assert_eq!(KING_DANGER[0], 0);
+    }
+
+    #[test]
+    fn safe_checks_are_dangerous() {
+        // The rook on e8 can safely check on e1 in the first position, but
+        // not in the second where the square is covered by the knight.
+        let safe_check = parse_fen("4r1k1/5ppp/8/8/5n1q/8/5PPP/6K1 w - - 0 1");
+        let unsafe_check = parse_fen("4r1k1/5ppp/8/8/5n1q/3N4/5PPP/6K1 w - - 0 1");
+
+        assert!(units(&safe_check) > units(&unsafe_check));
+    }
+
+    #[test]
+    fn checks_next_to_the_king_are_defended_by_it() {
+        // The rook's check on f1 is already covered by the king, so covering
+        // it with a knight as well doesn't make it any less safe.
+        let king_defended = parse_fen("5rk1/6pp/8/8/7q/8/6PP/6K1 w - - 0 1");
+        let knight_defended = parse_fen("5rk1/6pp/8/8/7q/4N3/6PP/6K1 w - - 0 1");
+
+        assert_eq!(units(&king_defended), units(&knight_defended));
+    }
+
+    #[test]
+    fn storming_pawns_are_dangerous() {
+        let storm = parse_fen("6k1/8/8/8/8/6pp/5PPP/6K1 w - - 0 1");
+        let no_storm = parse_fen("6k1/6pp/8/8/8/8/5PPP/6K1 w - - 0 1");
+
+        assert!(pawn_storm(Colour::White, Square::G1, &storm.board) > 0);
+        assert_eq!(pawn_storm(Colour::White, Square::G1, &no_storm.board), 0);
+    }
+
+    #[test]
+    fn checks_need_a_clear_line() {
+        // The rook on a8 is stuck behind the one on a3, so only the latter
+        // can check on a1.
+        let doubled = parse_fen("r5k1/5ppp/8/8/5n1q/r7/5PPP/6K1 w - - 0 1");
+        let single = parse_fen("6k1/5ppp/8/8/5n1q/r7/5PPP/6K1 w - - 0 1");
+
+        assert_eq!(units(&doubled), units(&single));
+    }
+
+    #[test]
+    fn king_should_be_close_to_the_pawns_in_the_endgame() {
+        let close = parse_fen("7k/8/8/8/8/4p3/3K4/8 w - - 0 1");
+        let far = parse_fen("7k/8/8/8/8/4p3/8/K7 w - - 0 1");
+
+        assert!(pawn_proximity(Square::D2, &close.board) < pawn_proximity(Square::A1, &far.board));
+    }
+
+    fn units(pos: &Position) -> usize {
+        attack_units(Colour::White, Square::G1, &pos.board, &Attacks::new(&pos.board))
+    }
+}
@@ -1,11 +1,24 @@
-use super::EvalTerm;
+use super::{Attacks, EvalTerm};
 use crate::colour::Colour;
 use crate::piece::Piece;
-use crate::position::Board;
+use crate::position::{Board, Position};
 
 pub const PIECE_WEIGHTS: [i32; 12] = [100, 300, 350, 500, 900, 0, 100, 300, 350, 500, 900, 0];
 
-pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
+// This is kept up to date incrementally in the position as pieces move, so
+// there's nothing to add up here.
+#[inline(always)]
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    pos.material[colour]
+}
+
+#[inline(always)]
+pub fn value(piece: Piece) -> EvalTerm {
+    EvalTerm::unphased(PIECE_WEIGHTS[piece])
+}
+
+// Add up the material from scratch.
+pub fn compute(colour: Colour, board: &Board) -> EvalTerm {
     let score = Piece::pieces_by_colour(colour).iter().fold(0, |acc, piece| {
         acc + PIECE_WEIGHTS[*piece] * board.count_pieces(*piece) as i32
     });
@@ -23,7 +36,18 @@ mod tests {
         let more_white_material = parse_fen("4kbnr/8/8/8/8/8/4P3/4KBNR w - - 0 1");
 
         assert!(
-            eval(Colour::White, &more_white_material.board).mg() > eval(Colour::Black, &more_white_material.board).mg()
+            eval(
+                Colour::White,
+                &more_white_material,
+                &Attacks::new(&more_white_material.board)
+            )
+            .mg()
+                > eval(
+                    Colour::Black,
+                    &more_white_material,
+                    &Attacks::new(&more_white_material.board)
+                )
+                .mg()
         );
     }
 
@@ -33,12 +57,32 @@ mod tests {
         let black_bishop_white_pawn = parse_fen("5b2/8/8/8/8/8/4P3/8 w - - 0 1");
 
         assert!(
-            eval(Colour::White, &white_knight_black_pawn.board).mg()
-                > eval(Colour::Black, &white_knight_black_pawn.board).mg()
+            eval(
+                Colour::White,
+                &white_knight_black_pawn,
+                &Attacks::new(&white_knight_black_pawn.board)
+            )
+            .mg()
+                > eval(
+                    Colour::Black,
+                    &white_knight_black_pawn,
+                    &Attacks::new(&white_knight_black_pawn.board)
+                )
+                .mg()
         );
         assert!(
-            eval(Colour::Black, &black_bishop_white_pawn.board).mg()
-                > eval(Colour::White, &black_bishop_white_pawn.board).mg()
+            eval(
+                Colour::Black,
+                &black_bishop_white_pawn,
+                &Attacks::new(&black_bishop_white_pawn.board)
+            )
+            .mg()
+                > eval(
+                    Colour::White,
+                    &black_bishop_white_pawn,
+                    &Attacks::new(&black_bishop_white_pawn.board)
+                )
+                .mg()
         );
     }
 
@@ -46,13 +90,19 @@ mod tests {
     fn rooks_are_worth_more_than_bishops() {
         let pos = parse_fen("5b2/8/8/8/8/8/8/7R w - - 0 1");
 
-        assert!(eval(Colour::White, &pos.board).mg() > eval(Colour::Black, &pos.board).mg());
+        assert!(
+            eval(Colour::White, &pos, &Attacks::new(&pos.board)).mg()
+                > eval(Colour::Black, &pos, &Attacks::new(&pos.board)).mg()
+        );
     }
 
     #[test]
     fn queens_are_worth_more_than_rooks() {
         let pos = parse_fen("7r/8/8/8/8/8/8/3Q4 w - - 0 1");
 
-        assert!(eval(Colour::White, &pos.board).mg() > eval(Colour::Black, &pos.board).mg());
+        assert!(
+            eval(Colour::White, &pos, &Attacks::new(&pos.board)).mg()
+                > eval(Colour::Black, &pos, &Attacks::new(&pos.board)).mg()
+        );
     }
 }
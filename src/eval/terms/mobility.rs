@@ -1,39 +1,124 @@
-use super::EvalTerm;
+use super::{Attacks, EvalTerm, pawn_attacks};
 use crate::colour::Colour;
-use crate::movegen::get_attacks;
 use crate::piece::Piece;
-use crate::position::Board;
+use crate::position::{Board, Position};
 use crate::square::Square;
 
-const KNIGHT_WEIGHTS: (i32, i32) = (4, 4);
-const BISHOP_WEIGHTS: (i32, i32) = (4, 4);
-const ROOK_WEIGHTS: (i32, i32) = (2, 3);
-const QUEEN_WEIGHTS: (i32, i32) = (1, 2);
+// Bonuses indexed by the number of safe squares a piece attacks. The first
+// few squares matter most, since a piece with nowhere to go is likely to be
+// trapped while one more square for an already active piece changes little.
+#[rustfmt::skip]
+const KNIGHT_MOBILITY: [(i32, i32); 9] = [
+    (-40, -50), (-17, -25), (-8, -15), (-1, -7), (5, -1), (11, 5), (15, 11), (20, 15), (24, 20),
+];
 
-pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
-    let occupancy = board.pieces_by_colour(colour);
+#[rustfmt::skip]
+const BISHOP_MOBILITY: [(i32, i32); 14] = [
+    (-35, -45), (-13, -19), (-4, -8), (3, 1), (9, 8), (15, 14), (19, 20), (24, 25), (28, 30),
+    (32, 34), (35, 38), (39, 42), (42, 46), (45, 50),
+];
 
-    let knights = mobility(Piece::knight(colour), KNIGHT_WEIGHTS, occupancy, board);
-    let bishops = mobility(Piece::bishop(colour), BISHOP_WEIGHTS, occupancy, board);
-    let rooks = mobility(Piece::rook(colour), ROOK_WEIGHTS, occupancy, board);
-    let queens = mobility(Piece::queen(colour), QUEEN_WEIGHTS, occupancy, board);
+#[rustfmt::skip]
+const ROOK_MOBILITY: [(i32, i32); 15] = [
+    (-30, -50), (-15, -21), (-9, -8), (-5, 1), (-1, 9), (3, 16), (6, 22), (9, 28), (12, 33),
+    (14, 38), (16, 43), (19, 48), (21, 52), (23, 56), (25, 60),
+];
+
+#[rustfmt::skip]
+const QUEEN_MOBILITY: [(i32, i32); 28] = [
+    (-20, -35), (-9, -15), (-5, -6), (-2, 0), (1, 5), (4, 10), (6, 14), (8, 18), (10, 22), (12, 26),
+    (13, 29), (15, 32), (17, 35), (18, 38), (20, 41), (21, 43), (22, 46), (24, 48), (25, 51),
+    (26, 53), (27, 55), (29, 58), (30, 60), (31, 62), (32, 64), (33, 66), (34, 68), (35, 70),
+];
+
+pub fn eval(colour: Colour, pos: &Position, attacks: &Attacks) -> EvalTerm {
+    let board = &pos.board;
+    let area = mobility_area(colour, board);
+
+    let knights = mobility(Piece::knight(colour), &KNIGHT_MOBILITY, area, board, attacks);
+    let bishops = mobility(Piece::bishop(colour), &BISHOP_MOBILITY, area, board, attacks);
+    let rooks = mobility(Piece::rook(colour), &ROOK_MOBILITY, area, board, attacks);
+    let queens = mobility(Piece::queen(colour), &QUEEN_MOBILITY, area, board, attacks);
 
     knights + bishops + rooks + queens
 }
 
+// The squares worth counting towards mobility. Those attacked by enemy pawns
+// aren't safe to move to, and our own king and blocked pawns won't be getting
+// out of the way any time soon.
+fn mobility_area(colour: Colour, board: &Board) -> u64 {
+    let them = colour.flip();
+    let our_pawns = board.pieces(Piece::pawn(colour));
+    let blocked_pawns = match colour {
+        Colour::White => our_pawns & (board.occupancy() >> 8),
+        _ => our_pawns & (board.occupancy() << 8),
+    };
+
+    !(pawn_attacks(them, board.pieces(Piece::pawn(them))) | blocked_pawns | board.pieces(Piece::king(colour)))
+}
+
 #[inline(always)]
-fn mobility(piece: Piece, weights: (i32, i32), occupancy: u64, board: &Board) -> EvalTerm {
+fn mobility(piece: Piece, table: &[(i32, i32)], area: u64, board: &Board, attacks: &Attacks) -> EvalTerm {
     let (mut mg, mut eg) = (0, 0);
     let mut pieces = board.pieces(piece);
 
     while pieces != 0 {
-        let square = Square::next(&mut pieces);
-        let attacks = get_attacks(piece, square, board) & !occupancy;
-        let mobility = attacks.count_ones() as i32;
+        let squares = attacks.x_rays_by_square(Square::next(&mut pieces)) & area;
+        let (bonus_mg, bonus_eg) = table[squares.count_ones() as usize];
 
-        mg += mobility * weights.0;
-        eg += mobility * weights.1;
+        mg += bonus_mg;
+        eg += bonus_eg;
     }
 
     EvalTerm::new(mg, eg)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn more_mobility_is_good() {
+        let active = parse_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let passive = parse_fen("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &active).mg() > eval_term(eval, Colour::White, &passive).mg());
+    }
+
+    #[test]
+    fn squares_attacked_by_enemy_pawns_are_not_safe() {
+        let safe = parse_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let unsafe_squares = parse_fen("4k3/3p4/6p1/8/3N4/8/8/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &safe).mg() > eval_term(eval, Colour::White, &unsafe_squares).mg());
+    }
+
+    #[test]
+    fn squares_with_blocked_pawns_are_not_counted() {
+        let blocked = parse_fen("4k3/8/8/8/2p5/2P5/8/B3K3 w - - 0 1");
+        let not_blocked = parse_fen("4k3/8/8/8/8/2P5/8/B3K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &not_blocked).mg() > eval_term(eval, Colour::White, &blocked).mg());
+    }
+
+    #[test]
+    fn rooks_x_ray_through_each_other() {
+        let doubled = parse_fen("4k3/8/8/8/8/8/R7/R3K3 w - - 0 1");
+        let blocked = parse_fen("4k3/8/8/8/8/8/N7/R3K3 w - - 0 1");
+        let attacks = Attacks::new(&doubled.board);
+
+        assert_eq!(
+            (attacks.x_rays_by_square(Square::A1) & mobility_area(Colour::White, &doubled.board)).count_ones(),
+            10
+        );
+        assert!(eval_term(eval, Colour::White, &doubled).mg() > eval_term(eval, Colour::White, &blocked).mg());
+    }
+
+    #[test]
+    fn a_trapped_piece_is_bad() {
+        let pos = parse_fen("4k3/8/8/8/8/1p6/PP6/N3K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &pos).mg() < 0);
+    }
+}
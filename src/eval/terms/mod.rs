@@ -1,25 +1,110 @@
 use crate::colour::Colour;
-use crate::position::Board;
+use crate::position::Position;
+use crate::square::{FILES, Square};
+use lazy_static::lazy_static;
 
+mod attacks;
+mod bad_bishops;
+mod bishop_pair;
+mod hanging;
 mod king;
-mod material;
+pub mod material;
 mod mobility;
+mod outposts;
 mod pawns;
-mod psqt;
+pub mod psqt;
+mod queen;
 mod rooks;
+mod tempo;
+mod threats;
 
+pub use attacks::Attacks;
 pub use material::PIECE_WEIGHTS;
 
-pub static TERMS: [fn(Colour, &Board) -> EvalTerm; 6] = [
-    material::eval,
-    mobility::eval,
-    psqt::eval,
-    king::eval,
-    pawns::eval,
-    rooks::eval,
+pub type Term = fn(Colour, &Position, &Attacks) -> EvalTerm;
+
+pub static TERMS: [(&str, Term); 13] = [
+    ("material", material::eval),
+    ("mobility", mobility::eval),
+    ("psqt", psqt::eval),
+    ("king", king::eval),
+    ("pawns", pawns::eval),
+    ("rooks", rooks::eval),
+    ("bishop pair", bishop_pair::eval),
+    ("bad bishops", bad_bishops::eval),
+    ("outposts", outposts::eval),
+    ("queen", queen::eval),
+    ("threats", threats::eval),
+    ("hanging", hanging::eval),
+    ("tempo", tempo::eval),
 ];
 
-#[derive(Debug, Clone, Copy)]
+// Every square attacked by the given pawns, whether occupied or not.
+pub fn pawn_attacks(colour: Colour, pawns: u64) -> u64 {
+    let (left, right) = (pawns & !FILES[0], pawns & !FILES[7]);
+
+    match colour {
+        Colour::White => (left << 7) | (right << 9),
+        _ => (left >> 9) | (right >> 7),
+    }
+}
+
+// The rank of a square from the point of view of the given colour, counting
+// from zero on the rank its pieces start on.
+pub fn relative_rank(colour: Colour, square: Square) -> usize {
+    match colour {
+        Colour::White => square.rank() as usize,
+        _ => 7 - square.rank() as usize,
+    }
+}
+
+pub const ADJACENT_FILES: [u64; 8] = [
+    FILES[1],
+    FILES[0] | FILES[2],
+    FILES[1] | FILES[3],
+    FILES[2] | FILES[4],
+    FILES[3] | FILES[5],
+    FILES[4] | FILES[6],
+    FILES[5] | FILES[7],
+    FILES[6],
+];
+
+lazy_static! {
+    pub static ref SQUARES_IN_FRONT: [[u64; 64]; 2] = [
+        build_squares_in_front(Colour::White),
+        build_squares_in_front(Colour::Black),
+    ];
+}
+
+fn build_squares_in_front(colour: Colour) -> [u64; 64] {
+    let mut masks = [0; 64];
+    let squares: [_; 64] = std::array::from_fn(|index| Square::from_index(index as u8));
+
+    for square in squares {
+        let rank = square.rank() as u32;
+
+        masks[square] = match colour {
+            Colour::White => {
+                if rank < 7 {
+                    !((1 << ((rank + 1) << 3)) - 1)
+                } else {
+                    0
+                }
+            }
+            _ => {
+                if rank > 0 {
+                    (1 << (rank << 3)) - 1
+                } else {
+                    0
+                }
+            }
+        };
+    }
+
+    masks
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EvalTerm(i32, i32);
 
 impl EvalTerm {
@@ -58,6 +143,13 @@ impl std::ops::Add for EvalTerm {
     }
 }
 
+impl std::ops::AddAssign for EvalTerm {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
 impl std::ops::Sub for EvalTerm {
     type Output = Self;
 
@@ -66,3 +158,10 @@ impl std::ops::Sub for EvalTerm {
         Self(self.mg() - rhs.mg(), self.eg() - rhs.eg())
     }
 }
+
+impl std::ops::SubAssign for EvalTerm {
+    #[inline(always)]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
@@ -0,0 +1,95 @@
+use super::{ADJACENT_FILES, Attacks, EvalTerm, SQUARES_IN_FRONT, pawn_attacks, relative_rank};
+use crate::colour::Colour;
+use crate::piece::Piece;
+use crate::position::{Board, Position};
+use crate::square::Square;
+
+const KNIGHT_OUTPOST_MG: i32 = 25;
+const KNIGHT_OUTPOST_EG: i32 = 15;
+
+const BISHOP_OUTPOST_MG: i32 = 12;
+const BISHOP_OUTPOST_EG: i32 = 6;
+
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    let board = &pos.board;
+    let knights = count_outposts(colour, Piece::knight(colour), board) as i32;
+    let bishops = count_outposts(colour, Piece::bishop(colour), board) as i32;
+
+    EvalTerm::new(
+        knights * KNIGHT_OUTPOST_MG + bishops * BISHOP_OUTPOST_MG,
+        knights * KNIGHT_OUTPOST_EG + bishops * BISHOP_OUTPOST_EG,
+    )
+}
+
+// An outpost is a square in the opponent's half of the board that is defended
+// by one of our pawns and can never be attacked by one of theirs.
+fn count_outposts(colour: Colour, piece: Piece, board: &Board) -> u32 {
+    let defended = pawn_attacks(colour, board.pieces(Piece::pawn(colour)));
+    let their_pawns = board.pieces(Piece::pawn(colour.flip()));
+    let mut pieces = board.pieces(piece) & defended;
+    let mut count = 0;
+
+    while pieces != 0 {
+        let square = Square::next(&mut pieces);
+
+        if !(3..=5).contains(&relative_rank(colour, square)) {
+            continue;
+        }
+
+        if their_pawns & ADJACENT_FILES[square.file() as usize] & SQUARES_IN_FRONT[colour][square] == 0 {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn knights_on_outposts_are_good() {
+        let pos = parse_fen("4k3/pp4pp/8/3N4/4P3/8/8/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &pos).mg() > 0);
+    }
+
+    #[test]
+    fn bishops_on_outposts_are_good_but_not_as_good_as_knights() {
+        let knight = parse_fen("4k3/pp4pp/8/3N4/4P3/8/8/4K3 w - - 0 1");
+        let bishop = parse_fen("4k3/pp4pp/8/3B4/4P3/8/8/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &bishop).mg() > 0);
+        assert!(eval_term(eval, Colour::White, &knight).mg() > eval_term(eval, Colour::White, &bishop).mg());
+    }
+
+    #[test]
+    fn squares_that_enemy_pawns_can_attack_are_not_outposts() {
+        let pos = parse_fen("4k3/pp2p1pp/8/3N4/4P3/8/8/4K3 w - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+    }
+
+    #[test]
+    fn undefended_squares_are_not_outposts() {
+        let pos = parse_fen("4k3/pp4pp/8/3N4/8/8/4P3/4K3 w - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+    }
+
+    #[test]
+    fn outposts_are_in_the_opponents_half() {
+        let pos = parse_fen("4k3/pp4pp/8/8/8/3N4/4P3/4K3 w - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+    }
+
+    #[test]
+    fn outposts_work_for_black() {
+        let pos = parse_fen("4k3/8/8/4p3/3n4/8/PP4PP/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::Black, &pos).mg() > 0);
+    }
+}
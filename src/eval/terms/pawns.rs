@@ -1,9 +1,8 @@
-use super::EvalTerm;
+use super::{ADJACENT_FILES, Attacks, EvalTerm, SQUARES_IN_FRONT, pawn_attacks, relative_rank};
 use crate::colour::Colour;
 use crate::piece::Piece;
-use crate::position::Board;
+use crate::position::{Board, Position};
 use crate::square::{FILES, Square};
-use lazy_static::lazy_static;
 
 const DOUBLED_MG: i32 = -12;
 const DOUBLED_EG: i32 = -8;
@@ -11,17 +10,39 @@ const DOUBLED_EG: i32 = -8;
 const ISOLATED_MG: i32 = -10;
 const ISOLATED_EG: i32 = -8;
 
-const PASSED_MG: [[i32; 8]; 2] = [
-    [0, 0, 5, 10, 20, 35, 60, 0], // White
-    [0, 60, 35, 20, 10, 5, 0, 0], // Black
-];
-const PASSED_EG: [[i32; 8]; 2] = [
-    [0, 5, 10, 20, 35, 60, 90, 0], // White
-    [0, 90, 60, 35, 20, 10, 5, 0], // Black
-];
-
-pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
-    doubled(colour, board) + isolated(colour, board) + passed(colour, board)
+const BACKWARD_MG: i32 = -8;
+const BACKWARD_EG: i32 = -6;
+
+// The rank based tables below are indexed by rank relative to the side the
+// pawn belongs to, so a pawn on its starting square is on rank 1.
+const CONNECTED_MG: [i32; 8] = [0, 3, 5, 8, 14, 24, 40, 0];
+const CONNECTED_EG: [i32; 8] = [0, 0, 3, 6, 12, 20, 30, 0];
+
+const CANDIDATE_MG: [i32; 8] = [0, 3, 5, 10, 16, 25, 0, 0];
+const CANDIDATE_EG: [i32; 8] = [0, 5, 8, 14, 22, 35, 0, 0];
+
+const PASSED_MG: [i32; 8] = [0, 0, 5, 10, 20, 35, 60, 0];
+const PASSED_EG: [i32; 8] = [0, 5, 10, 20, 35, 60, 90, 0];
+
+// How much the rank of a passed pawn scales the bonuses for its path and
+// the king distances, since these matter more the further advanced it is.
+const PASSED_WEIGHTS: [i32; 8] = [0, 0, 0, 1, 3, 5, 8, 0];
+
+const PASSED_FREE_PATH_EG: i32 = 5;
+const PASSED_BLOCKED_EG: i32 = -4;
+const PASSED_OUR_KING_DISTANCE_EG: i32 = -1;
+const PASSED_THEIR_KING_DISTANCE_EG: i32 = 2;
+
+const UNSTOPPABLE_EG: i32 = 400;
+
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    let board = &pos.board;
+    doubled(colour, board)
+        + isolated(colour, board)
+        + backward(colour, board)
+        + connected(colour, board)
+        + candidates(colour, board)
+        + passed(colour, board)
 }
 
 fn doubled(colour: Colour, board: &Board) -> EvalTerm {
@@ -61,68 +82,269 @@ fn isolated(colour: Colour, board: &Board) -> EvalTerm {
     EvalTerm::new(mg, eg)
 }
 
-fn passed(colour: Colour, board: &Board) -> EvalTerm {
+// A pawn is backward when the pawns on the adjacent files have all advanced
+// past it, so none can come to its support, and it can't safely advance to
+// catch up because an enemy pawn controls the square in front. Isolated pawns
+// are already penalised so they're left out.
+fn backward(colour: Colour, board: &Board) -> EvalTerm {
+    let (mut mg, mut eg) = (0, 0);
+    let our_pawns = board.pieces(Piece::pawn(colour));
+    let their_pawn_attacks = pawn_attacks(colour.flip(), board.pieces(Piece::pawn(colour.flip())));
+    let mut pawns = our_pawns;
+
+    while pawns != 0 {
+        let square = Square::next(&mut pawns);
+        let adjacent_pawns = our_pawns & ADJACENT_FILES[square.file() as usize];
+
+        if adjacent_pawns == 0 || adjacent_pawns & !SQUARES_IN_FRONT[colour][square] != 0 {
+            continue;
+        }
+
+        if their_pawn_attacks & square.advance(colour).u64() != 0 {
+            mg += BACKWARD_MG;
+            eg += BACKWARD_EG;
+        }
+    }
+
+    EvalTerm::new(mg, eg)
+}
+
+// Pawns that are defended by another pawn or stand side by side with one
+// (a phalanx) protect each other and control more squares together.
+fn connected(colour: Colour, board: &Board) -> EvalTerm {
     let (mut mg, mut eg) = (0, 0);
-    let mut our_pawns = board.pieces(Piece::pawn(colour));
+    let our_pawns = board.pieces(Piece::pawn(colour));
+    let supported = pawn_attacks(colour, our_pawns);
+    let phalanx = ((our_pawns & !FILES[7]) << 1) | ((our_pawns & !FILES[0]) >> 1);
+    let mut pawns = our_pawns & (supported | phalanx);
+
+    while pawns != 0 {
+        let rank = relative_rank(colour, Square::next(&mut pawns));
+        mg += CONNECTED_MG[rank];
+        eg += CONNECTED_EG[rank];
+    }
+
+    EvalTerm::new(mg, eg)
+}
+
+// A candidate passer is on a file with no enemy pawns in front of it and has
+// at least as many friendly pawns alongside or behind it on the adjacent
+// files as there are enemy pawns in front of it, so it can force its way
+// through to become passed.
+fn candidates(colour: Colour, board: &Board) -> EvalTerm {
+    let (mut mg, mut eg) = (0, 0);
+    let our_pawns = board.pieces(Piece::pawn(colour));
     let their_pawns = board.pieces(Piece::pawn(colour.flip()));
+    let mut pawns = our_pawns;
 
-    while our_pawns != 0 {
-        let square = Square::next(&mut our_pawns);
+    while pawns != 0 {
+        let square = Square::next(&mut pawns);
         let file = square.file() as usize;
-        let blockers = their_pawns & (FILES[file] | ADJACENT_FILES[file]);
+        let in_front = SQUARES_IN_FRONT[colour][square];
+
+        if their_pawns & FILES[file] & in_front != 0 || is_passed(colour, square, board) {
+            continue;
+        }
 
-        if blockers & SQUARES_IN_FRONT[colour][square] == 0 {
-            let rank = square.rank() as usize;
-            mg += PASSED_MG[colour][rank];
-            eg += PASSED_EG[colour][rank];
+        let helpers = our_pawns & ADJACENT_FILES[file] & !in_front;
+        let sentries = their_pawns & ADJACENT_FILES[file] & in_front;
+
+        if helpers.count_ones() >= sentries.count_ones() {
+            let rank = relative_rank(colour, square);
+            mg += CANDIDATE_MG[rank];
+            eg += CANDIDATE_EG[rank];
         }
     }
 
     EvalTerm::new(mg, eg)
 }
 
-const ADJACENT_FILES: [u64; 8] = [
-    FILES[1],
-    FILES[0] | FILES[2],
-    FILES[1] | FILES[3],
-    FILES[2] | FILES[4],
-    FILES[3] | FILES[5],
-    FILES[4] | FILES[6],
-    FILES[5] | FILES[7],
-    FILES[6],
-];
-
-lazy_static! {
-    static ref SQUARES_IN_FRONT: [[u64; 64]; 2] = [
-        build_squares_in_front(Colour::White),
-        build_squares_in_front(Colour::Black),
-    ];
-}
+fn passed(colour: Colour, board: &Board) -> EvalTerm {
+    let (mut mg, mut eg) = (0, 0);
+    let mut pawns = board.pieces(Piece::pawn(colour));
+    let our_king = Square::first(board.pieces(Piece::king(colour)));
+    let their_king = Square::first(board.pieces(Piece::king(colour.flip())));
 
-fn build_squares_in_front(colour: Colour) -> [u64; 64] {
-    let mut masks = [0; 64];
-    let squares: [_; 64] = std::array::from_fn(|index| Square::from_index(index as u8));
+    while pawns != 0 {
+        let square = Square::next(&mut pawns);
 
-    for square in squares {
-        let rank = square.rank() as u32;
+        if !is_passed(colour, square, board) {
+            continue;
+        }
 
-        masks[square] = match colour {
-            Colour::White => {
-                if rank < 7 {
-                    !((1 << ((rank + 1) << 3)) - 1)
-                } else {
-                    0
-                }
+        let rank = relative_rank(colour, square);
+        let weight = PASSED_WEIGHTS[rank];
+        let path = FILES[square.file() as usize] & SQUARES_IN_FRONT[colour][square];
+        let promotion_square = promotion_square(colour, square);
+
+        mg += PASSED_MG[rank];
+        eg += PASSED_EG[rank];
+
+        if board.has_occupancy_at(path) {
+            if board.has_piece_at(square.advance(colour)) {
+                eg += weight * PASSED_BLOCKED_EG;
             }
-            _ => {
-                if rank > 0 {
-                    (1 << (rank << 3)) - 1
-                } else {
-                    0
-                }
+        } else {
+            eg += weight * PASSED_FREE_PATH_EG;
+
+            if is_unstoppable(colour, square, promotion_square, board) {
+                eg += UNSTOPPABLE_EG;
             }
-        };
+        }
+
+        eg += weight * PASSED_OUR_KING_DISTANCE_EG * our_king.distance(promotion_square) as i32;
+        eg += weight * PASSED_THEIR_KING_DISTANCE_EG * their_king.distance(promotion_square) as i32;
+    }
+
+    EvalTerm::new(mg, eg)
+}
+
+// The rule of the square: in a pawn endgame, a passed pawn with a clear path
+// can't be caught if the enemy king is too far away from the promotion
+// square. Since the side to move isn't known here, the enemy king is assumed
+// to move first.
+fn is_unstoppable(colour: Colour, square: Square, promotion_square: Square, board: &Board) -> bool {
+    let them = colour.flip();
+    let their_pieces =
+        board.pieces_by_colour(them) & !board.pieces(Piece::pawn(them)) & !board.pieces(Piece::king(them));
+
+    if their_pieces != 0 {
+        return false;
     }
 
-    masks
+    // A pawn on its starting square can advance two squares on its first move.
+    let moves_to_promote = (7 - relative_rank(colour, square)).min(5) as u8;
+    let their_king = Square::first(board.pieces(Piece::king(them)));
+
+    their_king.distance(promotion_square) > moves_to_promote + 1
+}
+
+fn is_passed(colour: Colour, square: Square, board: &Board) -> bool {
+    let blockers = board.pieces(Piece::pawn(colour.flip()))
+        & (FILES[square.file() as usize] | ADJACENT_FILES[square.file() as usize]);
+
+    blockers & SQUARES_IN_FRONT[colour][square] == 0
+}
+
+fn promotion_square(colour: Colour, square: Square) -> Square {
+    match colour {
+        Colour::White => Square::from_file_and_rank(square.file(), 7),
+        _ => Square::from_file_and_rank(square.file(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn doubled_pawns_are_bad() {
+        let pos = parse_fen("4k3/8/8/8/8/4P3/4P3/4K3 w - - 0 1");
+
+        assert!(doubled(Colour::White, &pos.board).mg() < 0);
+        assert_eq!(doubled(Colour::Black, &pos.board).mg(), 0);
+    }
+
+    #[test]
+    fn isolated_pawns_are_bad() {
+        let isolated_pawn = parse_fen("4k3/8/8/8/8/8/P3P3/4K3 w - - 0 1");
+        let supported_pawn = parse_fen("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1");
+
+        assert!(isolated(Colour::White, &isolated_pawn.board).mg() < 0);
+        assert_eq!(isolated(Colour::White, &supported_pawn.board).mg(), 0);
+    }
+
+    #[test]
+    fn backward_pawns_are_bad() {
+        // The pawn on d2 can't be supported by the pawn on e4 and the square
+        // in front of it is attacked by the black pawn on c4.
+        let backward_pawn = parse_fen("4k3/8/8/8/2p1P3/8/3P4/4K3 w - - 0 1");
+        let not_backward_pawn = parse_fen("4k3/8/8/8/4P3/8/3P4/4K3 w - - 0 1");
+
+        assert!(backward(Colour::White, &backward_pawn.board).mg() < 0);
+        assert_eq!(backward(Colour::White, &not_backward_pawn.board).mg(), 0);
+    }
+
+    #[test]
+    fn connected_pawns_are_good() {
+        let phalanx = parse_fen("4k3/8/8/8/3PP3/8/8/4K3 w - - 0 1");
+        let supported = parse_fen("4k3/8/8/8/4P3/3P4/8/4K3 w - - 0 1");
+        let apart = parse_fen("4k3/8/8/8/4P3/8/2P5/4K3 w - - 0 1");
+
+        assert!(connected(Colour::White, &phalanx.board).mg() > 0);
+        assert!(connected(Colour::White, &supported.board).mg() > 0);
+        assert_eq!(connected(Colour::White, &apart.board).mg(), 0);
+    }
+
+    #[test]
+    fn candidate_passers_are_good() {
+        let candidate = parse_fen("4k3/8/8/8/2p5/8/1PP5/4K3 w - - 0 1");
+        let not_candidate = parse_fen("4k3/8/8/8/1pp5/8/2P5/4K3 w - - 0 1");
+
+        assert!(candidates(Colour::White, &candidate.board).eg() > 0);
+        assert_eq!(candidates(Colour::White, &not_candidate.board).eg(), 0);
+    }
+
+    #[test]
+    fn passed_pawns_are_good() {
+        let pos = parse_fen("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1");
+
+        assert!(passed(Colour::White, &pos.board).mg() > 0);
+        assert!(passed(Colour::White, &pos.board).eg() > 0);
+    }
+
+    #[test]
+    fn advanced_passed_pawns_are_better() {
+        let advanced = parse_fen("8/k7/3P4/8/8/8/8/K7 w - - 0 1");
+        let not_advanced = parse_fen("8/k7/8/8/8/3P4/8/K7 w - - 0 1");
+
+        assert!(passed(Colour::White, &advanced.board).eg() > passed(Colour::White, &not_advanced.board).eg());
+    }
+
+    #[test]
+    fn blocked_passed_pawns_are_worse() {
+        let free = parse_fen("1k6/8/3P4/8/8/8/8/3K3n w - - 0 1");
+        let blocked = parse_fen("1k6/3n4/3P4/8/8/8/8/3K4 w - - 0 1");
+
+        assert!(passed(Colour::White, &free.board).eg() > passed(Colour::White, &blocked.board).eg());
+    }
+
+    #[test]
+    fn passed_pawns_are_better_when_their_king_is_far_away() {
+        let close = parse_fen("8/2k5/8/3P4/8/8/8/3K3n w - - 0 1");
+        let far = parse_fen("8/8/8/3P4/8/8/8/3K2kn w - - 0 1");
+
+        assert!(passed(Colour::White, &far.board).eg() > passed(Colour::White, &close.board).eg());
+    }
+
+    #[test]
+    fn passed_pawns_outside_the_square_are_unstoppable() {
+        // The black king is outside the square of the pawn on a5, so it can't
+        // catch it even if Black is to move.
+        let outside = parse_fen("8/8/8/P7/8/8/5k2/7K w - - 0 1");
+        let inside = parse_fen("8/8/8/P7/3k4/8/8/7K w - - 0 1");
+
+        assert!(is_unstoppable(Colour::White, Square::A5, Square::A8, &outside.board));
+        assert!(!is_unstoppable(Colour::White, Square::A5, Square::A8, &inside.board));
+    }
+
+    #[test]
+    fn passed_pawns_are_not_unstoppable_while_they_have_pieces() {
+        let pos = parse_fen("8/8/8/P7/8/8/5k2/6nK w - - 0 1");
+
+        assert!(!is_unstoppable(Colour::White, Square::A5, Square::A8, &pos.board));
+    }
+
+    #[test]
+    fn pawn_structure_is_symmetrical() {
+        let pos = parse_fen("8/p4k2/1p3p2/2pP4/2P3P1/5K2/P6P/8 w - - 0 1");
+        let flipped = parse_fen("8/p6p/5k2/2p3p1/2Pp4/1P3P2/P4K2/8 w - - 0 1");
+
+        let white = eval_term(eval, Colour::White, &pos);
+        let black = eval_term(eval, Colour::Black, &flipped);
+
+        assert_eq!(white.mg(), black.mg());
+        assert_eq!(white.eg(), black.eg());
+    }
 }
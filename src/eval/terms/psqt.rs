@@ -1,44 +1,63 @@
-use super::EvalTerm;
+use super::{Attacks, EvalTerm};
 use crate::colour::Colour;
 use crate::piece::Piece;
-use crate::position::Board;
+use crate::position::{Board, Position};
 use crate::square::Square;
 use lazy_static::lazy_static;
 
 type Psqt = [i32; 64];
 
+// These are kept up to date incrementally in the position as pieces move, so
+// there's nothing to add up here.
 #[inline(always)]
-pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
-    let king_square = Square::first(board.pieces(Piece::king(colour)));
-    let king_term = EvalTerm::new(PSQT_MG_KING[colour][king_square], PSQT_EG_KING[colour][king_square]);
-
-    EvalTerm::unphased(eval_non_king(colour, board)) + king_term
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    pos.psqt[colour]
 }
 
+// The value of a piece on a square.
 #[inline(always)]
-fn eval_non_king(colour: Colour, board: &Board) -> i32 {
-    let pieces = [
-        Piece::pawn(colour),
-        Piece::knight(colour),
-        Piece::bishop(colour),
-        Piece::rook(colour),
-        Piece::queen(colour),
-    ];
-    pieces.iter().fold(0, |mut acc, piece| {
-        let mut pieces = board.pieces(*piece);
-        while pieces != 0 {
-            acc += PSQT_NON_KING[*piece][Square::next(&mut pieces)];
-        }
-        acc
-    })
+pub fn value(piece: Piece, square: Square) -> EvalTerm {
+    PIECE_SQUARE_VALUES[piece][square]
+}
+
+// Add up the values of every piece from scratch.
+pub fn compute(colour: Colour, board: &Board) -> EvalTerm {
+    Piece::pieces_by_colour(colour)
+        .iter()
+        .fold(EvalTerm::zero(), |mut acc, piece| {
+            let mut pieces = board.pieces(*piece);
+            while pieces != 0 {
+                acc += value(*piece, Square::next(&mut pieces));
+            }
+            acc
+        })
 }
 
 lazy_static! {
+    static ref PIECE_SQUARE_VALUES: [[EvalTerm; 64]; 12] = build_piece_square_values();
     static ref PSQT_NON_KING: [Psqt; 12] = build_psqt_non_king();
     static ref PSQT_MG_KING: [Psqt; 2] = build_psqt_king(&PSQT_MG_WHITE_KING);
     static ref PSQT_EG_KING: [Psqt; 2] = build_psqt_king(&PSQT_EG_WHITE_KING);
 }
 
+fn build_piece_square_values() -> [[EvalTerm; 64]; 12] {
+    let mut values = [[EvalTerm::zero(); 64]; 12];
+
+    for piece in Piece::pieces() {
+        for (square, value) in values[*piece].iter_mut().enumerate() {
+            *value = match piece.is_king() {
+                true => EvalTerm::new(
+                    PSQT_MG_KING[piece.colour()][square],
+                    PSQT_EG_KING[piece.colour()][square],
+                ),
+                false => EvalTerm::unphased(PSQT_NON_KING[*piece][square]),
+            };
+        }
+    }
+
+    values
+}
+
 #[inline(always)]
 fn build_psqt_non_king() -> [Psqt; 12] {
     let mut psqt = [[0; 64]; 12];
@@ -179,3 +198,32 @@ const SQUARE_MAP: [[usize; 64]; 2] = [
         56, 57, 58, 59, 60, 61, 62, 63,
     ],
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn values_are_mirrored_for_black() {
+        assert_eq!(value(Piece::WN, Square::E4), EvalTerm::unphased(15));
+        assert_eq!(value(Piece::BN, Square::E5), value(Piece::WN, Square::E4));
+    }
+
+    #[test]
+    fn values_are_computed_from_scratch_for_each_colour() {
+        let pos = parse_fen("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1");
+
+        assert_eq!(
+            compute(Colour::White, &pos.board),
+            value(Piece::WN, Square::E4) + value(Piece::WK, Square::E1)
+        );
+        assert_eq!(compute(Colour::Black, &pos.board), value(Piece::BK, Square::E8));
+    }
+
+    #[test]
+    fn king_should_be_central_in_the_endgame() {
+        assert!(value(Piece::WK, Square::D5).eg() > value(Piece::WK, Square::A1).eg());
+        assert!(value(Piece::BK, Square::E4).eg() > value(Piece::BK, Square::H8).eg());
+    }
+}
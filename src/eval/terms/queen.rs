@@ -0,0 +1,61 @@
+use super::{Attacks, EvalTerm};
+use crate::colour::Colour;
+use crate::piece::Piece;
+use crate::position::Position;
+use crate::square::Square;
+
+const UNDEVELOPED_MINOR_MG: i32 = -6;
+
+const QUEEN_START: [u64; 2] = [Square::D1.u64(), Square::D8.u64()];
+const MINOR_STARTS: [[u64; 2]; 2] = [
+    [Square::B1.u64() | Square::G1.u64(), Square::C1.u64() | Square::F1.u64()], // White knights, bishops
+    [Square::B8.u64() | Square::G8.u64(), Square::C8.u64() | Square::F8.u64()], // Black knights, bishops
+];
+
+// Bringing the queen out before the minor pieces lets the opponent develop
+// with tempo by attacking it, so penalise each minor piece still at home.
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    let board = &pos.board;
+    let queens = board.pieces(Piece::queen(colour));
+
+    if queens == 0 || queens & QUEEN_START[colour] != 0 {
+        return EvalTerm::zero();
+    }
+
+    let [knight_starts, bishop_starts] = MINOR_STARTS[colour];
+    let undeveloped = (board.pieces(Piece::knight(colour)) & knight_starts).count_ones()
+        + (board.pieces(Piece::bishop(colour)) & bishop_starts).count_ones();
+
+    EvalTerm::new(undeveloped as i32 * UNDEVELOPED_MINOR_MG, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn early_queen_development_is_bad() {
+        let pos = parse_fen("rnbqkbnr/pppp1ppp/8/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 1 2");
+
+        assert!(eval_term(eval, Colour::White, &pos).mg() < 0);
+        assert_eq!(eval_term(eval, Colour::White, &pos).eg(), 0);
+    }
+
+    #[test]
+    fn queen_development_after_the_minor_pieces_is_fine() {
+        let pos = parse_fen("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/2N2Q2/PPPP1PPP/R1B1K1NR w KQkq - 0 1");
+        let developed = parse_fen("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/2N2Q1N/PPPPBPPP/R3K2R w KQkq - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &pos).mg() < 0);
+        assert_eq!(eval_term(eval, Colour::White, &developed).mg(), 0);
+    }
+
+    #[test]
+    fn queen_at_home_is_fine() {
+        let pos = Position::startpos();
+
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+        assert_eq!(eval_term(eval, Colour::Black, &pos).mg(), 0);
+    }
+}
@@ -1,7 +1,8 @@
-use super::EvalTerm;
+use super::{Attacks, EvalTerm, relative_rank};
 use crate::colour::Colour;
+use crate::movegen::get_attacks;
 use crate::piece::Piece;
-use crate::position::Board;
+use crate::position::Position;
 use crate::square::{FILES, Square};
 
 const OPEN_FILE_MG: i32 = 15;
@@ -10,11 +11,20 @@ const OPEN_FILE_EG: i32 = 10;
 const HALF_OPEN_FILE_MG: i32 = 8;
 const HALF_OPEN_FILE_EG: i32 = 6;
 
-pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
+const SEVENTH_RANK_MG: i32 = 15;
+const SEVENTH_RANK_EG: i32 = 25;
+
+const CONNECTED_MG: i32 = 10;
+const CONNECTED_EG: i32 = 5;
+
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    let board = &pos.board;
     let (mut mg, mut eg) = (0, 0);
     let mut our_rooks = board.pieces(Piece::rook(colour));
     let our_pawns = board.pieces(Piece::pawn(colour));
-    let all_pawns = our_pawns | board.pieces(Piece::pawn(colour.flip()));
+    let their_pawns = board.pieces(Piece::pawn(colour.flip()));
+    let all_pawns = our_pawns | their_pawns;
+    let their_king = Square::first(board.pieces(Piece::king(colour.flip())));
 
     while our_rooks != 0 {
         let square = Square::next(&mut our_rooks);
@@ -27,7 +37,68 @@ pub fn eval(colour: Colour, board: &Board) -> EvalTerm {
             mg += HALF_OPEN_FILE_MG;
             eg += HALF_OPEN_FILE_EG;
         }
+
+        // A rook on the 7th is strong when it has pawns to attack there or
+        // keeps the king confined to the back rank.
+        if relative_rank(colour, square) == 6 {
+            let seventh_rank = 0xFF << (square.rank() * 8);
+            let back_rank = relative_rank(colour, their_king) == 7;
+
+            if back_rank || their_pawns & seventh_rank != 0 {
+                mg += SEVENTH_RANK_MG;
+                eg += SEVENTH_RANK_EG;
+            }
+        }
+
+        // Rooks defending each other, which is only counted from one side.
+        if our_rooks & get_attacks(Piece::rook(colour), square, board) != 0 {
+            mg += CONNECTED_MG;
+            eg += CONNECTED_EG;
+        }
     }
 
     EvalTerm::new(mg, eg)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn rooks_on_open_files_are_good() {
+        let open = parse_fen("4k3/p7/8/8/8/8/P7/1R2K3 w - - 0 1");
+        let half_open = parse_fen("4k3/1p6/8/8/8/8/P7/1R2K3 w - - 0 1");
+        let closed = parse_fen("4k3/8/8/8/8/8/PP6/1R2K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &open).mg() > eval_term(eval, Colour::White, &half_open).mg());
+        assert!(eval_term(eval, Colour::White, &half_open).mg() > eval_term(eval, Colour::White, &closed).mg());
+    }
+
+    #[test]
+    fn rooks_on_the_seventh_are_good() {
+        let pawns_on_seventh = parse_fen("8/pR6/8/8/8/8/P7/k3K3 w - - 0 1");
+        let king_on_back_rank = parse_fen("4k3/1R6/8/8/8/8/P7/4K3 w - - 0 1");
+        let neither = parse_fen("8/1R6/8/4k3/8/8/P7/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &pawns_on_seventh).eg() > eval_term(eval, Colour::White, &neither).eg());
+        assert!(
+            eval_term(eval, Colour::White, &king_on_back_rank).eg() > eval_term(eval, Colour::White, &neither).eg()
+        );
+    }
+
+    #[test]
+    fn rooks_on_the_seventh_work_for_black() {
+        let pos = parse_fen("4k3/8/8/8/8/8/3r3P/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::Black, &pos).eg() > 0);
+    }
+
+    #[test]
+    fn connected_rooks_are_good() {
+        let connected = parse_fen("4k3/8/8/8/8/8/PP6/R4RK1 w - - 0 1");
+        let not_connected = parse_fen("4k3/8/8/8/8/8/PP6/R3BRK1 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &connected).mg() > eval_term(eval, Colour::White, &not_connected).mg());
+    }
+}
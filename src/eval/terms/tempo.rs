@@ -0,0 +1,30 @@
+use super::{Attacks, EvalTerm};
+use crate::colour::Colour;
+use crate::position::Position;
+
+const TEMPO_MG: i32 = 15;
+const TEMPO_EG: i32 = 5;
+
+// A bonus for the side to move, which can improve its position before the
+// opponent gets the chance to.
+pub fn eval(colour: Colour, pos: &Position, _: &Attacks) -> EvalTerm {
+    if pos.colour_to_move != colour {
+        return EvalTerm::zero();
+    }
+
+    EvalTerm::new(TEMPO_MG, TEMPO_EG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn having_the_move_is_good() {
+        let pos = parse_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+
+        assert!(eval_term(eval, Colour::Black, &pos).mg() > 0);
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+    }
+}
@@ -0,0 +1,78 @@
+use super::{Attacks, EvalTerm};
+use crate::colour::Colour;
+use crate::piece::Piece;
+use crate::position::{Board, Position};
+
+// Threats by pawns, minor pieces and rooks, indexed by the type of the piece
+// being attacked (pawn, knight, bishop, rook, queen).
+const PAWN_THREATS: [(i32, i32); 5] = [(0, 0), (40, 30), (40, 30), (50, 35), (55, 40)];
+const MINOR_THREATS: [(i32, i32); 5] = [(0, 0), (0, 0), (0, 0), (30, 25), (35, 30)];
+const ROOK_THREATS: [(i32, i32); 5] = [(0, 0), (0, 0), (0, 0), (0, 0), (25, 20)];
+
+// Pieces attacked by something worth less than them are in trouble, since
+// they have to move away or lose material even if they're defended.
+pub fn eval(colour: Colour, pos: &Position, attacks: &Attacks) -> EvalTerm {
+    let board = &pos.board;
+    let minor_attacks = attacks.by_piece(Piece::knight(colour)) | attacks.by_piece(Piece::bishop(colour));
+
+    threats(colour, attacks.by_piece(Piece::pawn(colour)), &PAWN_THREATS, board)
+        + threats(colour, minor_attacks, &MINOR_THREATS, board)
+        + threats(colour, attacks.by_piece(Piece::rook(colour)), &ROOK_THREATS, board)
+}
+
+fn threats(colour: Colour, attacks: u64, weights: &[(i32, i32); 5], board: &Board) -> EvalTerm {
+    let (mut mg, mut eg) = (0, 0);
+
+    for (piece, (weight_mg, weight_eg)) in Piece::pieces_by_colour(colour.flip()).iter().zip(weights) {
+        let count = (board.pieces(*piece) & attacks).count_ones() as i32;
+        mg += count * weight_mg;
+        eg += count * weight_eg;
+    }
+
+    EvalTerm::new(mg, eg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn pawns_threatening_pieces_is_good() {
+        let pos = parse_fen("4k3/8/8/2n5/3P4/8/8/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &pos).mg() > 0);
+    }
+
+    #[test]
+    fn minor_pieces_threatening_rooks_and_queens_is_good() {
+        let rook = parse_fen("4k3/8/8/2r5/8/3N4/8/4K3 w - - 0 1");
+        let pawn = parse_fen("4k3/8/8/2p5/8/3N4/8/4K3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &rook).mg() > 0);
+        assert_eq!(eval_term(eval, Colour::White, &pawn).mg(), 0);
+    }
+
+    #[test]
+    fn rooks_threatening_queens_is_good() {
+        let queen = parse_fen("3qk3/8/8/8/8/8/8/3RK3 w - - 0 1");
+        let rook = parse_fen("3rk3/8/8/8/8/8/8/3RK3 w - - 0 1");
+
+        assert!(eval_term(eval, Colour::White, &queen).mg() > 0);
+        assert_eq!(eval_term(eval, Colour::White, &rook).mg(), 0);
+    }
+
+    #[test]
+    fn rooks_do_not_threaten_through_their_own_queen() {
+        let pos = parse_fen("3qk3/8/8/8/8/8/3Q4/3RK3 w - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::White, &pos), EvalTerm::zero());
+    }
+
+    #[test]
+    fn pieces_threatening_lesser_pieces_is_not_a_threat() {
+        let pos = parse_fen("4k3/8/8/2n5/8/8/8/2Q1K3 w - - 0 1");
+
+        assert_eq!(eval_term(eval, Colour::White, &pos).mg(), 0);
+    }
+}
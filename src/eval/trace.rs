@@ -0,0 +1,179 @@
+use super::eval;
+use super::phase::{MAX_PHASE, phase};
+use super::terms::{Attacks, EvalTerm, TERMS};
+use crate::colour::Colour;
+use crate::position::Position;
+use crate::square::Square;
+use std::fmt::{Display, Formatter, Result};
+
+const BOARD_RULE: &str = "   +-------+-------+-------+-------+-------+-------+-------+-------+";
+
+// A breakdown of the evaluation, with everything from White's point of view
+// unless stated otherwise.
+pub struct Trace {
+    pub terms: Vec<TermTrace>,
+    pub phase: i32,
+    pub eval: i32,
+    pub colour_to_move: Colour,
+    // How much each piece contributes to the eval, measured by removing it
+    // from the board and evaluating again.
+    pub piece_values: [Option<i32>; 64],
+}
+
+pub struct TermTrace {
+    pub name: &'static str,
+    pub white: EvalTerm,
+    pub black: EvalTerm,
+}
+
+pub fn trace(pos: &Position) -> Trace {
+    let attacks = Attacks::new(&pos.board);
+    let terms = TERMS
+        .iter()
+        .map(|(name, term)| TermTrace {
+            name,
+            white: term(Colour::White, pos, &attacks),
+            black: term(Colour::Black, pos, &attacks),
+        })
+        .collect();
+
+    Trace {
+        terms,
+        phase: phase(&pos.board),
+        eval: white_eval(pos),
+        colour_to_move: pos.colour_to_move,
+        piece_values: piece_values(pos),
+    }
+}
+
+// Go through the real eval so the trace can't disagree with it.
+fn white_eval(pos: &Position) -> i32 {
+    match pos.colour_to_move {
+        Colour::White => eval(pos),
+        _ => -eval(pos),
+    }
+}
+
+fn piece_values(pos: &Position) -> [Option<i32>; 64] {
+    let eval = white_eval(pos);
+    let mut values = [None; 64];
+
+    for (index, value) in values.iter_mut().enumerate() {
+        let square = Square::from_index(index as u8);
+
+        let Some(piece) = pos.board.piece_at(square) else {
+            continue;
+        };
+
+        // The eval assumes both kings are on the board.
+        if piece.is_king() {
+            continue;
+        }
+
+        let mut pos = pos.clone();
+        pos.remove_piece(square);
+
+        *value = Some(eval - white_eval(&pos));
+    }
+
+    values
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        writeln!(f, "\n     Term    |    White    |    Black    |    Total")?;
+        writeln!(f, "             |   MG    EG  |   MG    EG  |   MG    EG")?;
+        writeln!(f, " ------------+-------------+-------------+-------------")?;
+
+        let mut total = (EvalTerm::zero(), EvalTerm::zero());
+
+        for term in &self.terms {
+            write_row(f, term.name, term.white, term.black)?;
+            total = (total.0 + term.white, total.1 + term.black);
+        }
+
+        writeln!(f, " ------------+-------------+-------------+-------------")?;
+        write_row(f, "total", total.0, total.1)?;
+
+        let side_to_move_eval = match self.colour_to_move {
+            Colour::White => self.eval,
+            _ => -self.eval,
+        };
+
+        writeln!(f, "\nPhase: {}/{MAX_PHASE}", self.phase)?;
+        writeln!(f, "Final evaluation: {} (White side)", pawns(self.eval))?;
+        writeln!(f, "Final evaluation: {} (side to move)", pawns(side_to_move_eval))?;
+
+        writeln!(f, "\nPiece values (White side):")?;
+        writeln!(f, "{BOARD_RULE}")?;
+
+        for rank in (0..8).rev() {
+            write!(f, " {} |", rank + 1)?;
+
+            for file in 0..8 {
+                match self.piece_values[Square::from_file_and_rank(file, rank)] {
+                    Some(value) => write!(f, "{:>6} |", pawns(value))?,
+                    None => write!(f, "       |")?,
+                }
+            }
+
+            writeln!(f, "\n{BOARD_RULE}")?;
+        }
+
+        writeln!(f, "       a       b       c       d       e       f       g       h")
+    }
+}
+
+fn write_row(f: &mut Formatter, name: &str, white: EvalTerm, black: EvalTerm) -> Result {
+    let total = white - black;
+
+    writeln!(
+        f,
+        " {name:>11} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}",
+        white.mg(),
+        white.eg(),
+        black.mg(),
+        black.eg(),
+        total.mg(),
+        total.eg()
+    )
+}
+
+fn pawns(eval: i32) -> String {
+    format!("{:+.2}", eval as f64 / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::eval;
+    use crate::testing::*;
+
+    #[test]
+    fn trace_includes_every_term() {
+        let trace = trace(&Position::startpos());
+
+        assert_eq!(trace.terms.len(), TERMS.len());
+        assert_eq!(trace.terms[0].name, "material");
+    }
+
+    #[test]
+    fn traced_eval_matches_the_eval() {
+        let pos = parse_fen("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQK2R b KQkq - 0 5");
+        let trace = trace(&pos);
+
+        assert_eq!(trace.eval, -eval(&pos));
+        assert_eq!(trace.phase, MAX_PHASE);
+    }
+
+    #[test]
+    fn piece_values_are_from_white_side() {
+        let pos = parse_fen("4k3/8/8/3q4/8/8/8/3QK3 w - - 0 1");
+        let trace = trace(&pos);
+
+        assert!(trace.piece_values[Square::D1].unwrap() > 0);
+        assert!(trace.piece_values[Square::D5].unwrap() < 0);
+        assert_eq!(trace.piece_values[Square::E1], None);
+        assert_eq!(trace.piece_values[Square::A1], None);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::piece::Piece;
+use crate::position::Board;
+
+// Logistic model of the win probability for a given eval and amount of
+// material on the board, where the material is counted with the classical
+// piece values (pawn 1, minor 3, rook 5, queen 9) so the start position has
+// 78. Both the 50% win point and the spread grow or shrink with material,
+// modelled as quadratics in material relative to the reference amount.
+//
+// The parameters are a rough calibration rather than a fit to game results,
+// and the eval scale has changed since they were set, so treat win/draw/loss
+// figures as approximate. Scores are reported as the raw eval rather than
+// rescaled by this model until it has been fitted.
+const A_PARAMS: [f64; 3] = [457.84, -786.61, 414.36];
+const B_PARAMS: [f64; 3] = [199.58, -233.34, 255.22];
+
+const MIN_MATERIAL: u32 = 17;
+const MAX_MATERIAL: u32 = 78;
+const REFERENCE_MATERIAL: f64 = 58.0;
+
+const MATERIAL_VALUES: [u32; 12] = [1, 3, 3, 5, 9, 0, 1, 3, 3, 5, 9, 0];
+
+pub struct Wdl {
+    pub win: u32,
+    pub draw: u32,
+    pub loss: u32,
+}
+
+// Win/draw/loss chances in per mille for the side whose eval is given.
+pub fn wdl(eval: i32, material: u32) -> Wdl {
+    let win = win_rate(eval, material);
+    let loss = win_rate(-eval, material);
+
+    Wdl {
+        win,
+        draw: 1000 - win - loss,
+        loss,
+    }
+}
+
+pub fn material(board: &Board) -> u32 {
+    Piece::pieces()
+        .iter()
+        .map(|&piece| MATERIAL_VALUES[piece] * board.count_pieces(piece))
+        .sum()
+}
+
+fn win_rate(eval: i32, material: u32) -> u32 {
+    let (a, b) = win_rate_params(material);
+
+    (1000.0 / (1.0 + ((a - eval as f64) / b).exp())).round() as u32
+}
+
+fn win_rate_params(material: u32) -> (f64, f64) {
+    let m = material.clamp(MIN_MATERIAL, MAX_MATERIAL) as f64 / REFERENCE_MATERIAL;
+
+    let a = A_PARAMS[0] + A_PARAMS[1] * m + A_PARAMS[2] * m * m;
+    let b = B_PARAMS[0] + B_PARAMS[1] * m + B_PARAMS[2] * m * m;
+
+    (a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    #[test]
+    fn startpos_has_full_material() {
+        assert_eq!(material(&Position::startpos().board), MAX_MATERIAL);
+    }
+
+    #[test]
+    fn wdl_sums_to_one_thousand() {
+        for eval in [-10_000, -500, -100, 0, 37, 100, 500, 10_000] {
+            for material in [0, 17, 40, 78] {
+                let Wdl { win, draw, loss } = wdl(eval, material);
+                assert_eq!(win + draw + loss, 1000);
+            }
+        }
+    }
+
+    #[test]
+    fn wdl_is_symmetric() {
+        let white = wdl(150, 40);
+        let black = wdl(-150, 40);
+
+        assert_eq!(white.win, black.loss);
+        assert_eq!(white.draw, black.draw);
+        assert_eq!(white.loss, black.win);
+    }
+
+    #[test]
+    fn wdl_favours_the_side_with_the_higher_eval() {
+        let Wdl { win, loss, .. } = wdl(300, 40);
+        assert!(win > loss);
+
+        let Wdl { win, loss, .. } = wdl(0, 40);
+        assert_eq!(win, loss);
+    }
+}
@@ -8,16 +8,23 @@ mod position;
 mod rng;
 mod search;
 mod square;
+mod tree;
 mod uci;
 
 #[cfg(test)]
 mod testing;
 
 fn main() {
-    println!("{}, {}", info::name(), info::author());
-
     let args: Vec<_> = std::env::args().collect();
 
+    // The rendered tree is meant to be piped to other tools, so leave out the
+    // banner for it.
+    if args.get(1).map(|s| s.as_str()) == Some("tree") {
+        return tree::run(&args[2..]);
+    }
+
+    println!("{}, {}", info::name(), info::author());
+
     match args.get(1).map(|s| s.as_str()) {
         Some("bench") => bench::run(&args[2..]),
         _ => uci::main(),
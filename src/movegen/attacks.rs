@@ -63,10 +63,29 @@ fn get_knight_attacks(square: Square) -> u64 {
     KNIGHT_ATTACKS[square]
 }
 
+// Attacks for a sliding piece as if only the given squares were occupied,
+// e.g. to see x-ray attacks through pieces that have been left out.
+#[inline]
+pub fn get_slider_attacks(piece: Piece, square: Square, occupancy: u64) -> u64 {
+    match piece {
+        WB | BB => get_bishop_attacks_with_occupancy(square, occupancy),
+        WR | BR => get_rook_attacks_with_occupancy(square, occupancy),
+        WQ | BQ => {
+            get_bishop_attacks_with_occupancy(square, occupancy) | get_rook_attacks_with_occupancy(square, occupancy)
+        }
+        _ => panic!("not a sliding piece: {piece:?}"),
+    }
+}
+
 #[inline]
 fn get_bishop_attacks(square: Square, board: &Board) -> u64 {
+    get_bishop_attacks_with_occupancy(square, board.occupancy())
+}
+
+#[inline]
+fn get_bishop_attacks_with_occupancy(square: Square, occupancy: u64) -> u64 {
     let magic = &BISHOP_MAGICS[square];
-    let occupancy = board.occupancy() & magic.mask;
+    let occupancy = occupancy & magic.mask;
     let index = ((occupancy.wrapping_mul(magic.num)) >> magic.shift) as usize;
 
     BISHOP_ATTACKS[magic.offset + index]
@@ -74,8 +93,13 @@ fn get_bishop_attacks(square: Square, board: &Board) -> u64 {
 
 #[inline]
 fn get_rook_attacks(square: Square, board: &Board) -> u64 {
+    get_rook_attacks_with_occupancy(square, board.occupancy())
+}
+
+#[inline]
+fn get_rook_attacks_with_occupancy(square: Square, occupancy: u64) -> u64 {
     let magic = &ROOK_MAGICS[square];
-    let occupancy = board.occupancy() & magic.mask;
+    let occupancy = occupancy & magic.mask;
     let index = ((occupancy.wrapping_mul(magic.num)) >> magic.shift) as usize;
 
     ROOK_ATTACKS[magic.offset + index]
@@ -361,6 +385,18 @@ mod tests {
         assert_attacks_eq(&pos, "e1", &["d1", "f1", "d2", "e2", "f2"]);
     }
 
+    #[test]
+    fn slider_attacks_with_a_blocker_left_out() {
+        let pos = parse_fen("8/8/8/8/8/8/R7/R7 w - - 0 1");
+        let occupancy = pos.board.occupancy() & !Square::A2.u64();
+
+        assert_eq!(get_attacks(WR, Square::A1, &pos.board) & FILES[0], Square::A2.u64());
+        assert_eq!(
+            get_slider_attacks(WR, Square::A1, occupancy) & FILES[0],
+            FILES[0] & !Square::A1.u64()
+        );
+    }
+
     fn assert_attacks_eq(pos: &Position, attacker: &str, squares: &[&str]) {
         let attacker = attacker.parse().unwrap();
         let attacks: u64 = squares
@@ -92,6 +92,20 @@ pub fn generate_all_moves(pos: &Position) -> MoveList {
     moves
 }
 
+pub fn generate_legal_moves(pos: &mut Position) -> MoveList {
+    let colour_to_move = pos.colour_to_move;
+    let mut moves = generate_all_moves(pos);
+
+    moves.retain(|mv| {
+        pos.do_move(mv);
+        let is_legal = !is_in_check(colour_to_move, &pos.board);
+        pos.undo_move(mv);
+        is_legal
+    });
+
+    moves
+}
+
 pub fn generate_non_quiet_moves(pos: &Position) -> MoveList {
     let mut moves = MoveList::new();
     let colour_to_move = pos.colour_to_move;
@@ -372,7 +386,7 @@ mod tests {
 
         assert_castling_move_count(&moves, 1);
 
-        let castling_move = moves.iter().filter(|mv| mv.is_castling()).next().unwrap();
+        let castling_move = moves.iter().find(|mv| mv.is_castling()).unwrap();
 
         assert_eq!(castling_move.from, Square::E1);
         assert_eq!(castling_move.to, Square::C1);
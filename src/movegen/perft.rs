@@ -1,47 +1,245 @@
-use super::{generate_all_moves, is_in_check};
+use super::{Move, generate_legal_moves, is_in_check};
 use crate::position::Position;
-use crate::uci::r#move::UciMove;
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-pub fn perft(pos: &mut Position, depth: u8) -> u128 {
+pub const DEFAULT_HASH_MB: usize = 64;
+
+pub fn perft(pos: &mut Position, depth: u8, table: &mut PerftTable) -> u128 {
     if depth == 0 {
         return 1;
     }
 
-    let mut nodes = 0;
+    // Bulk counting: the legal moves at the last ply are the leaves, so
+    // there's no need to make each of them just to count one.
+    if depth == 1 {
+        return generate_legal_moves(pos).len() as u128;
+    }
 
-    for mv in generate_all_moves(pos) {
-        pos.do_move(&mv);
+    if let Some(nodes) = table.probe(pos.key, depth) {
+        return nodes;
+    }
 
-        if !is_in_check(pos.opponent_colour(), &pos.board) {
-            nodes += perft(pos, depth - 1);
-        }
+    let mut nodes = 0;
 
+    for mv in generate_legal_moves(pos) {
+        pos.do_move(&mv);
+        nodes += perft(pos, depth - 1, table);
         pos.undo_move(&mv);
     }
 
+    table.store(pos.key, depth, nodes);
+
     nodes
 }
 
-pub fn divide(pos: &mut Position, depth: u8) -> u128 {
+// Count the nodes below each root move, in move generation order. Root moves
+// are shared between threads as they become free, and each thread has its
+// own share of the hash table, of at least 1 MB unless hashing is disabled.
+pub fn divide(pos: &Position, depth: u8, threads: usize, hash_mb: usize) -> Vec<(Move, u128)> {
+    let moves = generate_legal_moves(&mut pos.clone());
+
     if depth == 0 {
-        return 1;
+        return vec![];
     }
 
-    let mut nodes = 0;
+    let threads = threads.clamp(1, moves.len().max(1));
+    let next_move = AtomicUsize::new(0);
+
+    // The table is never probed below depth 2, so don't pay for allocating it.
+    let thread_hash_mb = match hash_mb {
+        0 => 0,
+        _ if depth <= 2 => 0,
+        _ => (hash_mb / threads).max(1),
+    };
+
+    let mut counts: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut pos = pos.clone();
+                    let mut table = PerftTable::new(thread_hash_mb);
+                    let mut counts = vec![];
+
+                    while let Some(mv) = moves.get(next_move.fetch_add(1, Ordering::Relaxed)) {
+                        pos.do_move(mv);
+                        counts.push((*mv, perft(&mut pos, depth - 1, &mut table)));
+                        pos.undo_move(mv);
+                    }
 
-    for mv in generate_all_moves(pos) {
+                    counts
+                })
+            })
+            .collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    });
+
+    counts.sort_by_key(|(mv, _)| moves.iter().position(|other| other == mv));
+    counts
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: u128,
+    pub captures: u128,
+    pub en_passants: u128,
+    pub castles: u128,
+    pub promotions: u128,
+    pub checks: u128,
+    pub checkmates: u128,
+}
+
+// Count the leaves by the type of move leading to them, as in the reference
+// tables at https://www.chessprogramming.org/Perft_Results. This needs every
+// leaf to be made, so has neither bulk counting nor hashing.
+pub fn perft_stats(pos: &mut Position, depth: u8) -> PerftStats {
+    let mut stats = PerftStats::default();
+
+    if depth == 0 {
+        stats.nodes = 1;
+        return stats;
+    }
+
+    for mv in generate_legal_moves(pos) {
         pos.do_move(&mv);
 
-        if !is_in_check(pos.opponent_colour(), &pos.board) {
-            let count = perft(pos, depth - 1);
-            println!("{}: {}", UciMove::from(mv), count);
-            nodes += count;
+        if depth > 1 {
+            stats += perft_stats(pos, depth - 1);
+        } else {
+            let is_check = is_in_check(pos.colour_to_move, &pos.board);
+
+            stats.nodes += 1;
+            stats.captures += mv.captured_piece.is_some() as u128;
+            stats.en_passants += mv.is_en_passant as u128;
+            stats.castles += mv.is_castling() as u128;
+            stats.promotions += mv.promotion_piece.is_some() as u128;
+            stats.checks += is_check as u128;
+            stats.checkmates += (is_check && generate_legal_moves(pos).is_empty()) as u128;
         }
 
         pos.undo_move(&mv);
     }
 
-    nodes
+    stats
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passants += other.en_passants;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+// A position from an EPD perft suite, given as the FEN followed by the
+// expected node counts for each depth, e.g. `<fen> ;D1 20 ;D2 400`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SuiteEntry {
+    pub fen: String,
+    pub counts: Vec<(u8, u128)>,
+}
+
+pub fn parse_suite(input: &str) -> Result<Vec<SuiteEntry>, String> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| parse_suite_entry(line).map_err(|err| format!("{err} on line {}", index + 1)))
+        .collect()
+}
+
+fn parse_suite_entry(line: &str) -> Result<SuiteEntry, String> {
+    let mut fields = line.split(';').map(str::trim);
+    let fen = fields.next().unwrap_or_default();
+
+    // EPD positions usually leave out the move counters.
+    let fen = match fen.split_whitespace().count() {
+        4 => format!("{fen} 0 1"),
+        _ => fen.to_string(),
+    };
+
+    let counts = fields
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            let count = field
+                .strip_prefix('D')
+                .and_then(|count| count.split_once(char::is_whitespace))
+                .and_then(|(depth, nodes)| Some((depth.parse().ok()?, nodes.trim().parse().ok()?)));
+
+            count.ok_or(format!("invalid perft count '{field}'"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if counts.is_empty() {
+        return Err("missing perft counts".to_string());
+    }
+
+    Ok(SuiteEntry { fen, counts })
+}
+
+// Node counts by position and depth, replacing whatever was there before.
+// The depth is mixed into the index so that the counts for different depths
+// of the same position don't compete for a slot.
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct PerftEntry {
+    key: u64,
+    depth: u8,
+    nodes: u64,
+}
+
+impl PerftTable {
+    // A size of zero disables the table.
+    pub fn new(size_mb: usize) -> Self {
+        let capacity = size_mb
+            .checked_mul(1024 * 1024)
+            .map_or(0, |size_bytes| size_bytes / std::mem::size_of::<PerftEntry>());
+
+        // Round down to a power of two so that indexing can mask the key.
+        let size = if capacity == 0 { 0 } else { 1 << capacity.ilog2() };
+
+        Self {
+            entries: vec![PerftEntry::default(); size],
+        }
+    }
+
+    fn probe(&self, key: u64, depth: u8) -> Option<u128> {
+        let entry = self.entries.get(self.index(key, depth)?)?;
+
+        if entry.key == key && entry.depth == depth {
+            Some(entry.nodes as u128)
+        } else {
+            None
+        }
+    }
+
+    fn store(&mut self, key: u64, depth: u8, nodes: u128) {
+        if let Some(index) = self.index(key, depth) {
+            self.entries[index] = PerftEntry {
+                key,
+                depth,
+                nodes: nodes as u64,
+            };
+        }
+    }
+
+    fn index(&self, key: u64, depth: u8) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let mixed = key ^ (depth as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        Some(mixed as usize & (self.entries.len() - 1))
+    }
 }
 
 #[cfg(test)]
@@ -50,25 +248,138 @@ mod tests {
     use crate::position::START_POS_FEN;
     use crate::testing::*;
 
+    const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const POSITION_4_FEN: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+
     #[test]
     fn perft_start_position_shallow() {
-        assert_perft(START_POS_FEN, 4, 197_281);
+        assert_unhashed_perft(START_POS_FEN, 4, 197_281);
+    }
+
+    #[test]
+    fn count_no_nodes_below_checkmate_or_stalemate() {
+        for fen in ["7k/6Q1/6K1/8/8/8/8/8 b - - 0 1", "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"] {
+            let pos = parse_fen(fen);
+
+            for depth in 1..=4 {
+                assert_eq!(perft(&mut pos.clone(), depth, &mut PerftTable::new(1)), 0);
+                assert!(divide(&pos, depth, 2, 1).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn round_the_hash_table_size_down_to_a_power_of_two() {
+        assert!(PerftTable::new(0).entries.is_empty());
+
+        for size_mb in [1, 2, 3, 64] {
+            let capacity = size_mb * 1024 * 1024 / std::mem::size_of::<PerftEntry>();
+            let size = PerftTable::new(size_mb).entries.len();
+
+            assert!(size.is_power_of_two());
+            assert!(size <= capacity && size * 2 > capacity);
+        }
+    }
+
+    #[test]
+    fn count_the_same_nodes_with_a_hash_table() {
+        let mut pos = parse_fen(KIWIPETE_FEN);
+        let mut table = PerftTable::new(1);
+
+        assert_eq!(perft(&mut pos, 3, &mut PerftTable::new(0)), 97_862);
+        assert_eq!(perft(&mut pos, 3, &mut table), 97_862);
+        assert_eq!(perft(&mut pos, 3, &mut table), 97_862);
+    }
+
+    #[test]
+    fn divide_the_root_moves_between_threads() {
+        let pos = parse_fen(KIWIPETE_FEN);
+
+        let counts = divide(&pos, 3, 4, 4);
+
+        assert_eq!(counts.len(), 48);
+        assert_eq!(counts.iter().map(|(_, nodes)| nodes).sum::<u128>(), 97_862);
+        assert_eq!(
+            counts.iter().map(|(mv, _)| *mv).collect::<Vec<_>>(),
+            generate_legal_moves(&mut pos.clone()).to_vec()
+        );
+    }
+
+    #[test]
+    fn count_leaves_by_move_type() {
+        assert_eq!(
+            perft_stats(&mut parse_fen(KIWIPETE_FEN), 2),
+            PerftStats {
+                nodes: 2_039,
+                captures: 351,
+                en_passants: 1,
+                castles: 91,
+                promotions: 0,
+                checks: 3,
+                checkmates: 0,
+            }
+        );
+
+        assert_eq!(
+            perft_stats(&mut parse_fen(POSITION_4_FEN), 3),
+            PerftStats {
+                nodes: 9_467,
+                captures: 1_021,
+                en_passants: 4,
+                castles: 0,
+                promotions: 120,
+                checks: 38,
+                checkmates: 22,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_a_perft_suite() {
+        let suite = "\
+# Positions from https://www.chessprogramming.org/Perft_Results
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400
+
+8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ;D1 14
+";
+
+        assert_eq!(
+            parse_suite(suite),
+            Ok(vec![
+                SuiteEntry {
+                    fen: START_POS_FEN.to_string(),
+                    counts: vec![(1, 20), (2, 400)],
+                },
+                SuiteEntry {
+                    fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1".to_string(),
+                    counts: vec![(1, 14)],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn reject_invalid_perft_counts() {
+        assert_eq!(
+            parse_suite(&format!("{START_POS_FEN}\n{START_POS_FEN} ;D1 x")),
+            Err("missing perft counts on line 1".to_string())
+        );
+        assert_eq!(
+            parse_suite(&format!("{START_POS_FEN} ;D1 20\n{START_POS_FEN} ;D1 x")),
+            Err("invalid perft count 'D1 x' on line 2".to_string())
+        );
     }
 
     #[test]
     #[ignore]
     fn perft_start_position() {
-        assert_perft(START_POS_FEN, 6, 119_060_324);
+        assert_unhashed_perft(START_POS_FEN, 6, 119_060_324);
     }
 
     #[test]
     #[ignore]
     fn perft_position_2() {
-        assert_perft(
-            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
-            5,
-            193_690_690,
-        );
+        assert_perft(KIWIPETE_FEN, 5, 193_690_690);
     }
 
     #[test]
@@ -80,11 +391,7 @@ mod tests {
     #[test]
     #[ignore]
     fn perft_position_4() {
-        assert_perft(
-            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
-            6,
-            706_045_033,
-        );
+        assert_perft(POSITION_4_FEN, 6, 706_045_033);
     }
 
     #[test]
@@ -117,7 +424,17 @@ mod tests {
         );
     }
 
+    // Checks move generation on its own, without the hash table or threads.
+    fn assert_unhashed_perft(fen: &str, depth: u8, expected_move_count: u128) {
+        assert_eq!(
+            perft(&mut parse_fen(fen), depth, &mut PerftTable::new(0)),
+            expected_move_count
+        );
+    }
+
     fn assert_perft(fen: &str, depth: u8, expected_move_count: u128) {
-        assert_eq!(perft(&mut parse_fen(fen), depth), expected_move_count);
+        let counts = divide(&parse_fen(fen), depth, 4, DEFAULT_HASH_MB);
+
+        assert_eq!(counts.iter().map(|(_, nodes)| nodes).sum::<u128>(), expected_move_count);
     }
 }
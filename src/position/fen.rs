@@ -94,8 +94,12 @@ impl std::str::FromStr for Position {
             return Err(format!("FEN must contain {NUM_PARTS} parts, got {}", parts.len()));
         }
 
-        let half_move_clock = parts[4].parse().unwrap();
-        let full_move_counter = parts[5].parse().unwrap();
+        let half_move_clock = parts[4]
+            .parse()
+            .map_err(|_| format!("invalid half move clock '{}'", parts[4]))?;
+        let full_move_counter = parts[5]
+            .parse()
+            .map_err(|_| format!("invalid full move counter '{}'", parts[5]))?;
 
         Ok(Position::new(
             parse_board(parts[0])?,
@@ -117,18 +121,28 @@ fn parse_board(str: &str) -> Result<Board, String> {
 
     let mut board = Board::empty();
     let mut square_index = Square::A8.index();
+    let mut file = 0;
 
     for char in str.chars() {
         if char == '/' {
+            if file != 8 {
+                return Err("board must contain 64 squares".to_string());
+            }
             square_index -= 16;
+            file = 0;
             continue;
         }
 
         if char.is_ascii_digit() {
             square_index += char as u8 - b'0';
+            file += char as u8 - b'0';
             continue;
         }
 
+        if file >= 8 {
+            return Err("board must contain 64 squares".to_string());
+        }
+
         let piece = match char {
             'P' => Piece::WP,
             'N' => Piece::WN,
@@ -147,9 +161,10 @@ fn parse_board(str: &str) -> Result<Board, String> {
 
         board.put_piece(piece, Square::from_index(square_index));
         square_index += 1;
+        file += 1;
     }
 
-    if square_index != 8 {
+    if file != 8 || square_index != 8 {
         return Err("board must contain 64 squares".to_string());
     }
 
@@ -333,6 +348,18 @@ mod tests {
         assert_eq!(pos.full_move_counter, 20);
     }
 
+    #[test]
+    fn parse_error_with_too_many_squares_in_a_row() {
+        assert_parse_error("pppppppppp/8/8/8/8/8/8/8 w - - 0 1", "board must contain 64 squares");
+        assert_parse_error("9/8/8/8/8/8/8/7 w - - 0 1", "board must contain 64 squares");
+    }
+
+    #[test]
+    fn parse_error_with_invalid_move_counters() {
+        assert_parse_error("8/8/8/8/8/8/8/8 w - - x 1", "invalid half move clock 'x'");
+        assert_parse_error("8/8/8/8/8/8/8/8 w - - 0 -1", "invalid full move counter '-1'");
+    }
+
     fn assert_parse_error(fen: &str, err: &str) {
         let parse = fen.parse::<Position>();
 
@@ -1,7 +1,8 @@
 use crate::colour::Colour;
-use crate::movegen::{Move, get_en_passant_attacks};
+use crate::eval::terms::{EvalTerm, material, psqt};
+use crate::movegen::{Move, get_en_passant_attacks, is_in_check};
 use crate::piece::Piece;
-use crate::square::Square;
+use crate::square::{BACK_RANKS, Square};
 use smallvec::SmallVec;
 
 mod board;
@@ -24,8 +25,12 @@ pub struct Position {
     pub castling_rights: CastlingRights,
     pub en_passant_square: Option<Square>,
     pub half_move_clock: u8,
-    pub full_move_counter: u8,
+    pub full_move_counter: u16,
     pub key: u64,
+    // Material and piece-square values for each colour, kept up to date as
+    // pieces move so the eval doesn't have to add them up every time.
+    pub material: [EvalTerm; 2],
+    pub psqt: [EvalTerm; 2],
     history: SmallVec<[HistoryEntry; MAX_HISTORY]>,
 }
 
@@ -34,7 +39,10 @@ struct HistoryEntry {
     castling_rights: CastlingRights,
     en_passant_square: Option<Square>,
     half_move_clock: u8,
+    full_move_counter: u16,
     key: u64,
+    material: [EvalTerm; 2],
+    psqt: [EvalTerm; 2],
 }
 
 impl Position {
@@ -44,7 +52,7 @@ impl Position {
         castling_rights: CastlingRights,
         en_passant_square: Option<Square>,
         half_move_clock: u8,
-        full_move_counter: u8,
+        full_move_counter: u16,
     ) -> Self {
         let mut pos = Self {
             board,
@@ -54,9 +62,13 @@ impl Position {
             half_move_clock,
             full_move_counter,
             key: 0,
+            material: [EvalTerm::zero(); 2],
+            psqt: [EvalTerm::zero(); 2],
             history: SmallVec::new(),
         };
         pos.key = pos.compute_key();
+        pos.material = pos.compute_material();
+        pos.psqt = pos.compute_psqt();
         pos
     }
 
@@ -69,7 +81,10 @@ impl Position {
             castling_rights: self.castling_rights,
             en_passant_square: self.en_passant_square,
             half_move_clock: self.half_move_clock,
+            full_move_counter: self.full_move_counter,
             key: self.key,
+            material: self.material,
+            psqt: self.psqt,
         };
         self.history.push(history);
 
@@ -80,12 +95,11 @@ impl Position {
         }
 
         self.en_passant_square = None;
-        self.half_move_clock += 1;
+        self.half_move_clock = self.half_move_clock.saturating_add(1);
 
         if let Some(capture_square) = mv.capture_square() {
             self.half_move_clock = 0;
-            self.board.remove_piece(capture_square);
-            self.key ^= ZOBRIST.piece_square[mv.captured_piece.unwrap()][capture_square];
+            self.remove_piece(capture_square);
         }
 
         if mv.piece.is_pawn() {
@@ -112,21 +126,15 @@ impl Position {
                         let rook_to = Square::from_file_and_rank(3, mv.to.rank());
                         let rook_from = Square::from_file_and_rank(0, mv.to.rank());
 
-                        self.board.put_piece(rook, rook_to);
-                        self.board.remove_piece(rook_from);
-
-                        self.key ^= ZOBRIST.piece_square[rook][rook_to];
-                        self.key ^= ZOBRIST.piece_square[rook][rook_from];
+                        self.put_piece(rook, rook_to);
+                        self.remove_piece(rook_from);
                     }
                     Square::G1 | Square::G8 => {
                         let rook_to = Square::from_file_and_rank(5, mv.to.rank());
                         let rook_from = Square::from_file_and_rank(7, mv.to.rank());
 
-                        self.board.put_piece(rook, rook_to);
-                        self.board.remove_piece(rook_from);
-
-                        self.key ^= ZOBRIST.piece_square[rook][rook_to];
-                        self.key ^= ZOBRIST.piece_square[rook][rook_from];
+                        self.put_piece(rook, rook_to);
+                        self.remove_piece(rook_from);
                     }
                     _ => unreachable!(),
                 };
@@ -144,21 +152,19 @@ impl Position {
         self.key ^= ZOBRIST.castling_rights[self.castling_rights];
         self.key ^= ZOBRIST.castling_rights[history.castling_rights];
 
-        let to_piece = mv.promotion_piece.unwrap_or(mv.piece);
-        self.board.put_piece(to_piece, mv.to);
-        self.board.remove_piece(mv.from);
-
-        self.key ^= ZOBRIST.piece_square[to_piece][mv.to];
-        self.key ^= ZOBRIST.piece_square[mv.piece][mv.from];
+        self.put_piece(mv.promotion_piece.unwrap_or(mv.piece), mv.to);
+        self.remove_piece(mv.from);
 
         if self.colour_to_move == Colour::Black {
-            self.full_move_counter += 1;
+            self.full_move_counter = self.full_move_counter.saturating_add(1);
         }
 
         self.colour_to_move = self.opponent_colour();
         self.key ^= ZOBRIST.colour_to_move;
 
         debug_assert_eq!(self.key, self.compute_key());
+        debug_assert_eq!(self.material, self.compute_material());
+        debug_assert_eq!(self.psqt, self.compute_psqt());
     }
 
     pub fn undo_move(&mut self, mv: &Move) {
@@ -166,7 +172,10 @@ impl Position {
         self.castling_rights = history.castling_rights;
         self.en_passant_square = history.en_passant_square;
         self.half_move_clock = history.half_move_clock;
+        self.full_move_counter = history.full_move_counter;
         self.key = history.key;
+        self.material = history.material;
+        self.psqt = history.psqt;
 
         if mv.is_castling() {
             let rook = Piece::rook(self.opponent_colour());
@@ -199,11 +208,9 @@ impl Position {
 
         self.colour_to_move = self.opponent_colour();
 
-        if self.colour_to_move == Colour::Black {
-            self.full_move_counter -= 1;
-        }
-
         debug_assert_eq!(self.key, self.compute_key());
+        debug_assert_eq!(self.material, self.compute_material());
+        debug_assert_eq!(self.psqt, self.compute_psqt());
     }
 
     pub fn do_null_move(&mut self) {
@@ -211,7 +218,10 @@ impl Position {
             castling_rights: self.castling_rights,
             en_passant_square: self.en_passant_square,
             half_move_clock: self.half_move_clock,
+            full_move_counter: self.full_move_counter,
             key: self.key,
+            material: self.material,
+            psqt: self.psqt,
         };
         self.history.push(history);
 
@@ -222,10 +232,10 @@ impl Position {
         }
 
         self.en_passant_square = None;
-        self.half_move_clock += 1;
+        self.half_move_clock = self.half_move_clock.saturating_add(1);
 
         if self.colour_to_move == Colour::Black {
-            self.full_move_counter += 1;
+            self.full_move_counter = self.full_move_counter.saturating_add(1);
         }
 
         self.colour_to_move = self.opponent_colour();
@@ -239,18 +249,99 @@ impl Position {
         self.castling_rights = history.castling_rights;
         self.en_passant_square = history.en_passant_square;
         self.half_move_clock = history.half_move_clock;
+        self.full_move_counter = history.full_move_counter;
         self.key = history.key;
 
         self.colour_to_move = self.opponent_colour();
 
-        if self.colour_to_move == Colour::Black {
-            self.full_move_counter -= 1;
+        debug_assert_eq!(self.key, self.compute_key());
+    }
+
+    // Put a piece on an empty square, keeping the key, material and
+    // piece-square values in sync with the board.
+    pub fn put_piece(&mut self, piece: Piece, square: Square) {
+        self.board.put_piece(piece, square);
+        self.key ^= ZOBRIST.piece_square[piece][square];
+        self.material[piece.colour()] += material::value(piece);
+        self.psqt[piece.colour()] += psqt::value(piece, square);
+    }
+
+    pub fn remove_piece(&mut self, square: Square) {
+        let Some(piece) = self.board.piece_at(square) else {
+            return;
+        };
+        self.board.remove_piece(square);
+        self.key ^= ZOBRIST.piece_square[piece][square];
+        self.material[piece.colour()] -= material::value(piece);
+        self.psqt[piece.colour()] -= psqt::value(piece, square);
+    }
+
+    pub fn compute_material(&self) -> [EvalTerm; 2] {
+        [
+            material::compute(Colour::White, &self.board),
+            material::compute(Colour::Black, &self.board),
+        ]
+    }
+
+    pub fn compute_psqt(&self) -> [EvalTerm; 2] {
+        [
+            psqt::compute(Colour::White, &self.board),
+            psqt::compute(Colour::Black, &self.board),
+        ]
+    }
+
+    // Check that the position is playable, i.e. that move generation and search
+    // can rely on the invariants of a legal chess position. Parsing a FEN only
+    // checks the syntax, so positions received from a GUI should pass this too.
+    pub fn validate(&self) -> Result<(), String> {
+        for colour in [Colour::White, Colour::Black] {
+            if self.board.count_pieces(Piece::king(colour)) != 1 {
+                return Err("each side must have exactly one king".to_string());
+            }
         }
 
-        debug_assert_eq!(self.key, self.compute_key());
+        if (self.board.pieces(Piece::WP) | self.board.pieces(Piece::BP)) & BACK_RANKS != 0 {
+            return Err("pawns cannot be on the first or last rank".to_string());
+        }
+
+        if is_in_check(self.opponent_colour(), &self.board) {
+            return Err("the side not to move is in check".to_string());
+        }
+
+        if let Some(square) = self.en_passant_square {
+            let expected_rank = match self.colour_to_move {
+                Colour::White => 5,
+                _ => 2,
+            };
+            let pawn_square = square.advance(self.opponent_colour());
+
+            if square.rank() != expected_rank
+                || self.board.has_piece_at(square)
+                || self.board.piece_at(pawn_square) != Some(Piece::pawn(self.opponent_colour()))
+            {
+                return Err("invalid en passant square".to_string());
+            }
+        }
+
+        let castling = [
+            (CastlingRight::WhiteKing, Piece::WK, Square::E1, Piece::WR, Square::H1),
+            (CastlingRight::WhiteQueen, Piece::WK, Square::E1, Piece::WR, Square::A1),
+            (CastlingRight::BlackKing, Piece::BK, Square::E8, Piece::BR, Square::H8),
+            (CastlingRight::BlackQueen, Piece::BK, Square::E8, Piece::BR, Square::A8),
+        ];
+
+        for (right, king, king_square, rook, rook_square) in castling {
+            if self.castling_rights.has(right)
+                && (self.board.piece_at(king_square) != Some(king) || self.board.piece_at(rook_square) != Some(rook))
+            {
+                return Err("invalid castling rights".to_string());
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn is_repetition_draw(&self, search_ply: u8) -> bool {
+    pub fn is_repetition_draw(&self, search_ply: u16) -> bool {
         if self.half_move_clock < 8 {
             return false;
         }
@@ -283,6 +374,18 @@ impl Position {
         self.half_move_clock >= 100
     }
 
+    // A key for the position along with everything that decides whether it can
+    // still be drawn: the half-move clock and the positions since the last
+    // irreversible move, which are the only ones that can be repeated. Adding
+    // the earlier keys up means transpositions share the same key.
+    pub fn key_with_history(&self) -> u64 {
+        self.history
+            .iter()
+            .rev()
+            .take(self.half_move_clock as usize)
+            .fold(self.key ^ self.half_move_clock as u64, |key, h| key.wrapping_add(h.key))
+    }
+
     pub fn opponent_colour(&self) -> Colour {
         self.colour_to_move.flip()
     }
@@ -736,6 +839,125 @@ mod tests {
         assert_eq!(pos.full_move_counter, 1);
     }
 
+    #[test]
+    fn stop_the_full_move_counter_at_its_maximum() {
+        let mut pos = parse_fen("k7/8/8/8/8/8/8/7K b - - 0 65535");
+
+        let mv = make_move(Piece::BK, Square::A8, Square::A7, None);
+        pos.do_move(&mv);
+
+        assert_eq!(pos.full_move_counter, u16::MAX);
+
+        pos.undo_move(&mv);
+        pos.do_null_move();
+
+        assert_eq!(pos.full_move_counter, u16::MAX);
+
+        pos.undo_null_move();
+
+        assert_eq!(pos.full_move_counter, u16::MAX);
+    }
+
+    #[test]
+    fn keep_the_material_and_piece_square_values_up_to_date() {
+        let mut pos = parse_fen("1n2k3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+        let (material, psqt) = (pos.material, pos.psqt);
+
+        let mv = Move {
+            piece: Piece::WP,
+            from: Square::C7,
+            to: Square::B8,
+            captured_piece: Some(Piece::BN),
+            promotion_piece: Some(Piece::WQ),
+            is_en_passant: false,
+        };
+        pos.do_move(&mv);
+
+        assert_eq!(pos.material, pos.compute_material());
+        assert_eq!(pos.material[Colour::White], EvalTerm::unphased(900));
+        assert_eq!(pos.psqt, pos.compute_psqt());
+        assert_eq!(pos.psqt[Colour::Black], psqt::value(Piece::BK, Square::E8));
+
+        pos.undo_move(&mv);
+
+        assert_eq!(pos.material, material);
+        assert_eq!(pos.psqt, psqt);
+    }
+
+    #[test]
+    fn remove_a_piece() {
+        let mut pos = parse_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1");
+
+        pos.remove_piece(Square::D1);
+
+        assert!(!pos.board.has_piece_at(Square::D1));
+        assert_eq!(pos.key, pos.compute_key());
+        assert_eq!(pos.material, pos.compute_material());
+        assert_eq!(pos.psqt, pos.compute_psqt());
+    }
+
+    #[test]
+    fn validate_a_playable_position() {
+        assert!(Position::startpos().validate().is_ok());
+        assert!(parse_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").validate().is_ok());
+    }
+
+    #[test]
+    fn validate_the_number_of_kings() {
+        assert_validate_error("8/8/8/8/8/8/8/4K3 w - - 0 1", "each side must have exactly one king");
+        assert_validate_error("4k3/8/8/8/8/8/8/3KK3 w - - 0 1", "each side must have exactly one king");
+    }
+
+    #[test]
+    fn validate_pawns_are_not_on_back_ranks() {
+        assert_validate_error(
+            "3Pk3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "pawns cannot be on the first or last rank",
+        );
+    }
+
+    #[test]
+    fn validate_the_side_not_to_move_is_not_in_check() {
+        assert_validate_error("4k3/8/8/8/8/8/8/4RK2 w - - 0 1", "the side not to move is in check");
+        assert_validate_error("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1", "the side not to move is in check");
+    }
+
+    #[test]
+    fn validate_the_en_passant_square() {
+        assert_validate_error("4k3/8/8/3pP3/8/8/8/4K3 b - d6 0 1", "invalid en passant square");
+        assert_validate_error("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1", "invalid en passant square");
+    }
+
+    #[test]
+    fn validate_castling_rights_against_piece_placement() {
+        assert_validate_error("4k3/8/8/8/8/8/8/4K3 w K - 0 1", "invalid castling rights");
+        assert_validate_error("r3k3/8/8/8/8/8/8/4K3 w k - 0 1", "invalid castling rights");
+    }
+
+    #[test]
+    fn key_the_history_since_the_last_irreversible_move() {
+        let pos = Position::startpos();
+        let mut shuffled = pos.clone();
+
+        for mv in [
+            make_move(Piece::WN, Square::G1, Square::F3, None), // Nf3
+            make_move(Piece::BN, Square::G8, Square::F6, None), // Nf6
+            make_move(Piece::WN, Square::F3, Square::G1, None), // Ng1
+            make_move(Piece::BN, Square::F6, Square::G8, None), // Ng8
+        ] {
+            shuffled.do_move(&mv);
+        }
+
+        assert_eq!(shuffled.key, pos.key);
+        assert_ne!(shuffled.key_with_history(), pos.key_with_history());
+
+        shuffled.do_move(&make_move(Piece::WP, Square::E2, Square::E4, None));
+        let mut pushed = pos.clone();
+        pushed.do_move(&make_move(Piece::WP, Square::E2, Square::E4, None));
+
+        assert_eq!(shuffled.key_with_history(), pushed.key_with_history());
+    }
+
     #[test]
     fn detect_repetition_draw_from_start_position() {
         let mut pos = Position::startpos();
@@ -752,7 +974,7 @@ mod tests {
         ];
 
         for (index, mv) in moves.iter_mut().enumerate() {
-            pos.do_move(&mv);
+            pos.do_move(mv);
 
             let expect_repetition_draw = index == 7;
             assert_eq!(
@@ -781,7 +1003,7 @@ mod tests {
         ];
 
         for (index, mv) in moves.iter_mut().enumerate() {
-            pos.do_move(&mv);
+            pos.do_move(mv);
 
             let expect_repetition_draw = index == 7;
             assert_eq!(
@@ -811,7 +1033,7 @@ mod tests {
         ];
 
         for (index, mv) in moves.iter_mut().enumerate() {
-            pos.do_move(&mv);
+            pos.do_move(mv);
 
             assert!(
                 !pos.is_repetition_draw(0),
@@ -839,7 +1061,7 @@ mod tests {
         ];
 
         for (index, mv) in moves.iter_mut().enumerate() {
-            pos.do_move(&mv);
+            pos.do_move(mv);
 
             assert!(
                 !pos.is_repetition_draw(0),
@@ -848,4 +1070,8 @@ mod tests {
             );
         }
     }
+
+    fn assert_validate_error(fen: &str, err: &str) {
+        assert_eq!(parse_fen(fen).validate(), Err(err.to_string()));
+    }
 }
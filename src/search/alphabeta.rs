@@ -1,6 +1,7 @@
 use super::{
     history::HISTORY_SCORE_MAX,
     movepicker::{MovePicker, MovePickerMode},
+    tree::Cutoff,
     tt::Bound,
     *,
 };
@@ -12,29 +13,74 @@ use smallvec::SmallVec;
 
 const LMR_HISTORY_THRESHOLD: i32 = HISTORY_SCORE_MAX / 4;
 const LMP_THRESHOLDS: [u8; 5] = [0, 5, 9, 14, 21];
+const PROGRESS_NODES_MASK: u128 = 4095;
+
+#[inline(always)]
+pub fn search<E: Evaluator>(
+    ss: &mut SearchState<E>,
+    pos: &mut Position,
+    depth: u8,
+    alpha: i32,
+    beta: i32,
+    ply: u16,
+) -> i32 {
+    // Keep the cost of recording the tree out of normal searches.
+    let Some(tree) = &mut ss.tree else {
+        return search_node(ss, pos, depth, alpha, beta, ply);
+    };
+
+    tree.enter(ply, depth, alpha, beta);
+    let eval = search_node(ss, pos, depth, alpha, beta, ply);
+    ss.record(|tree| tree.exit(eval));
+
+    eval
+}
 
 #[rustfmt::skip]
-pub fn search(
-    ss: &mut SearchState,
+fn search_node<E: Evaluator>(
+    ss: &mut SearchState<E>,
     pos: &mut Position,
     mut depth: u8,
     mut alpha: i32,
-    beta: i32,
-    ply: u8,
+    mut beta: i32,
+    ply: u16,
 ) -> i32 {
     ss.pv.clear(ply);
 
     if ss.stopper.should_stop(&ss.report) {
+        ss.record(|tree| tree.cutoff(Cutoff::Stopped));
         return 0;
     }
 
     if pos.is_fifty_move_draw() || pos.is_repetition_draw(ply) {
-        return EVAL_DRAW;
+        ss.record(|tree| tree.cutoff(Cutoff::Draw));
+        return ss.draw_eval(pos);
+    }
+
+    if ply >= MAX_PLY {
+        return ss.evaluator.eval(pos);
+    }
+
+    let is_pv_node = beta - alpha > 1;
+
+    // Mate distance pruning: nothing found from here can be better than
+    // mating at the next ply or worse than being mated at this one, so narrow
+    // the window accordingly. If a shorter mate has already been found then
+    // this node can't improve on it.
+    if ply > 0 {
+        alpha = alpha.max(-EVAL_MATE + ply as i32);
+        beta = beta.min(EVAL_MATE - ply as i32 - 1);
+
+        if alpha >= beta {
+            ss.record(|tree| tree.cutoff(Cutoff::MateDistance));
+            return alpha;
+        }
     }
 
     if depth == 0 {
         if !is_in_check(pos.colour_to_move, &pos.board) {
-            return quiescence::search(pos, alpha, beta, &mut ss.report);
+            ss.record(|tree| tree.cutoff(Cutoff::Quiescence));
+            return quiescence::search(ss, pos, alpha, beta, ply);
         }
 
         // Extend the search if we're in check so that quiescence doesn't need
@@ -42,26 +88,46 @@ pub fn search(
         depth = 1;
     }
 
-    let is_pv_node = beta - alpha > 1;
     let mut tt_move = None;
 
+    ss.count(|stats| stats.tt_probes += 1);
+
     if let Some(entry) = ss.tt.probe(pos.key) {
+        // The entry borrows the table, so count through the fields directly.
+        if ss.params.stats {
+            ss.report.stats.tt_hits += 1;
+        }
+
         // Don't cut off at PV nodes since we need to build the full PV.
         if !is_pv_node && entry.depth >= depth {
             let eval = tt::eval_out(entry.eval, ply);
 
-            match entry.bound {
-                Bound::Exact => return eval,
-                Bound::Lower if eval >= beta => return beta,
-                Bound::Upper if eval <= alpha => return alpha,
-                _ => (),
+            let cutoff = match entry.bound {
+                Bound::Exact => Some(eval),
+                Bound::Lower if eval >= beta => Some(beta),
+                Bound::Upper if eval <= alpha => Some(alpha),
+                _ => None,
             };
+
+            if let Some(eval) = cutoff {
+                if ss.params.stats {
+                    ss.report.stats.tt_cutoff(&entry.bound);
+                }
+                ss.record(|tree| tree.cutoff(Cutoff::Tt));
+                return eval;
+            }
         }
 
-        tt_move = entry.mv;
+        tt_move = entry.mv.filter(|mv| !ss.is_excluded(ply, mv));
     }
 
     ss.report.nodes += 1;
+    ss.report.seldepth = ss.report.seldepth.max(ply);
+
+    if ss.report.nodes & PROGRESS_NODES_MASK == 0 {
+        ss.report.tt_usage = ss.tt.usage();
+        ss.reporter.send_progress(&ss.report);
+    }
 
     let colour_to_move = pos.colour_to_move;
     let in_check = is_in_check(colour_to_move, &pos.board);
@@ -76,7 +142,7 @@ pub fn search(
         && alpha > -EVAL_MATE_THRESHOLD
         && beta < EVAL_MATE_THRESHOLD
     {
-        let eval = eval(pos);
+        let eval = ss.evaluator.eval(pos);
 
         // Reverse futility pruning: if the static eval is already well above
         // beta at shallow depths, assume this node will fail high.
@@ -86,6 +152,8 @@ pub fn search(
         };
         if safe_to_prune && eval - depth as i32 * 100 >= beta {
             ss.tt.store(pos.key, depth, tt::eval_in(beta, ply), Bound::Lower, tt_move);
+            ss.count(|stats| stats.rfp_prunes += 1);
+            ss.record(|tree| tree.cutoff(Cutoff::Rfp));
             return beta;
         }
 
@@ -97,15 +165,19 @@ pub fn search(
     // Null-move pruning: if not in check and with sufficient depth/material, try
     // a null move to quickly detect beta cutoffs.
     if depth >= 3 && !in_check && has_non_pawn_material(&pos.board, colour_to_move) {
+        ss.count(|stats| stats.null_move_tries += 1);
         pos.do_null_move();
 
         let reduction = if depth > 6 { 3 } else { 2 };
+        ss.record(|tree| tree.next(None, reduction));
         let eval = -search(ss, pos, depth - reduction - 1, -beta, -beta + 1, ply + 1);
 
         pos.undo_null_move();
 
         if eval >= beta {
             ss.tt.store(pos.key, depth, tt::eval_in(eval, ply), Bound::Lower, None);
+            ss.count(|stats| stats.null_move_cutoffs += 1);
+            ss.record(|tree| tree.cutoff(Cutoff::NullMove));
             return beta;
         }
     }
@@ -118,11 +190,22 @@ pub fn search(
     // Search the TT move before generating other moves because there's a good
     // chance it leads to a cutoff
     if let Some(mv) = tt_move {
-        pos.do_move(&mv);
+        let nodes_before = ss.report.nodes;
+
+        if ply == 0 {
+            ss.reporter.send_current_move(&ss.report, mv, 1);
+        }
+
+        ss.do_move(pos, &mv);
+        ss.record(|tree| tree.next(Some(mv), 0));
 
         let eval = -search(ss, pos, depth - 1, -beta, -alpha, ply + 1);
 
-        pos.undo_move(&mv);
+        ss.undo_move(pos, &mv);
+
+        if ply == 0 {
+            ss.add_root_move_nodes(&mv, (ss.report.nodes - nodes_before) as u64);
+        }
 
         if eval >= beta {
             if mv.is_quiet() {
@@ -132,7 +215,13 @@ pub fn search(
                 ss.history.store(history_bonus, mv.piece, mv.to);
             }
 
+            if ply == 0 {
+                ss.pv.update(ply, mv);
+            }
+
             ss.tt.store(pos.key, depth, tt::eval_in(eval, ply), Bound::Lower, tt_move);
+            ss.count(|stats| stats.fail_high(1));
+            ss.record(|tree| tree.cutoff(Cutoff::Beta));
             return beta;
         }
 
@@ -140,6 +229,12 @@ pub fn search(
             alpha = eval;
             tt_bound = Bound::Exact;
             ss.pv.update(ply, mv);
+
+            // Remember the best root move so far in case the iteration is
+            // stopped before it completes.
+            if ply == 0 && ss.excluded_root_moves.is_empty() && !ss.stopper.should_stop(&ss.report) {
+                ss.root_pv = Some((ss.pv.root().clone(), eval));
+            }
         }
 
         if mv.is_quiet() {
@@ -160,19 +255,25 @@ pub fn search(
     );
 
     while let Some(mv) = move_picker.pick() {
-        if tt_move.is_some() && mv.equals(&tt_move.unwrap()) {
+        if (tt_move.is_some() && mv.equals(&tt_move.unwrap())) || ss.is_excluded(ply, &mv) {
             continue;
         }
 
-        pos.do_move(&mv);
+        let nodes_before = ss.report.nodes;
+
+        ss.do_move(pos, &mv);
 
         if is_in_check(colour_to_move, &pos.board) {
-            pos.undo_move(&mv);
+            ss.undo_move(pos, &mv);
             continue;
         }
 
         move_number += 1;
 
+        if ply == 0 {
+            ss.reporter.send_current_move(&ss.report, mv, move_number);
+        }
+
         let gives_check = is_in_check(pos.colour_to_move, &pos.board);
 
         // Late move pruning: skip searching quiet moves late in the move list
@@ -184,7 +285,9 @@ pub fn search(
             && mv.is_quiet()
             && move_number >= LMP_THRESHOLDS[depth as usize]
         {
-            pos.undo_move(&mv);
+            ss.undo_move(pos, &mv);
+            ss.count(|stats| stats.lmp_prunes += 1);
+            ss.record(|tree| tree.prune(mv, depth - 1, -beta, -alpha, Cutoff::Lmp));
             continue;
         }
 
@@ -196,7 +299,9 @@ pub fn search(
             && let Some(eval) = futility_base_eval
             && eval + depth as i32 * 100 <= alpha
         {
-            pos.undo_move(&mv);
+            ss.undo_move(pos, &mv);
+            ss.count(|stats| stats.futility_prunes += 1);
+            ss.record(|tree| tree.prune(mv, depth - 1, -beta, -alpha, Cutoff::Futility));
             continue;
         }
 
@@ -226,24 +331,37 @@ pub fn search(
                 0
             };
 
+            if reduction > 0 {
+                ss.count(|stats| stats.lmr_searches += 1);
+            }
+
+            ss.record(|tree| tree.next(Some(mv), reduction));
             eval = -search(ss, pos, depth - reduction - 1, -alpha - 1, -alpha, ply + 1);
 
             // If the reduced search raised alpha then re-search at full depth
             // to see if the move is actually good.
             if eval > alpha && reduction > 0 {
+                ss.count(|stats| stats.lmr_re_searches += 1);
+                ss.record(|tree| tree.next(Some(mv), 0));
                 eval = -search(ss, pos, depth - 1, -alpha - 1, -alpha, ply + 1);
             }
 
             // If the zero-window PVS raised alpha then re-search at full window
             // to obtain the exact eval and PV.
             if eval > alpha && eval < beta {
+                ss.record(|tree| tree.next(Some(mv), 0));
                 eval = -search(ss, pos, depth - 1, -beta, -alpha, ply + 1);
             }
         } else {
+            ss.record(|tree| tree.next(Some(mv), 0));
             eval = -search(ss, pos, depth - 1, -beta, -alpha, ply + 1);
         }
 
-        pos.undo_move(&mv);
+        ss.undo_move(pos, &mv);
+
+        if ply == 0 {
+            ss.add_root_move_nodes(&mv, (ss.report.nodes - nodes_before) as u64);
+        }
 
         if eval >= beta {
             if mv.is_quiet() {
@@ -257,7 +375,14 @@ pub fn search(
                 }
             }
 
+            // Keep the move that failed high at the root for reporting.
+            if ply == 0 {
+                ss.pv.update(ply, mv);
+            }
+
             ss.tt.store(pos.key, depth, tt::eval_in(eval, ply), Bound::Lower, Some(mv));
+            ss.count(|stats| stats.fail_high(move_number));
+            ss.record(|tree| tree.cutoff(Cutoff::Beta));
             return beta;
         }
 
@@ -270,13 +395,17 @@ pub fn search(
             tt_bound = Bound::Exact;
             tt_move = Some(mv);
             ss.pv.update(ply, mv);
+
+            if ply == 0 && ss.excluded_root_moves.is_empty() && !ss.stopper.should_stop(&ss.report) {
+                ss.root_pv = Some((ss.pv.root().clone(), eval));
+            }
         }
 
         has_searched_one = true;
     }
 
     if move_number == 0 {
-        return if in_check { -EVAL_MATE + ply as i32 } else { EVAL_DRAW };
+        return if in_check { -EVAL_MATE + ply as i32 } else { ss.draw_eval(pos) };
     }
 
     ss.tt.store(pos.key, depth, tt::eval_in(alpha, ply), tt_bound, tt_move);
@@ -1,26 +1,26 @@
-use super::MAX_DEPTH;
+use super::MAX_PLY;
 use crate::movegen::Move;
 
 pub struct KillerMoves {
-    moves: [[Option<Move>; 2]; MAX_DEPTH as usize],
+    moves: [[Option<Move>; 2]; MAX_PLY as usize],
 }
 
 impl KillerMoves {
     pub fn new() -> Self {
         Self {
-            moves: [[None; 2]; MAX_DEPTH as usize],
+            moves: [[None; 2]; MAX_PLY as usize],
         }
     }
 
-    pub fn probe(&self, ply: u8, index: usize) -> Option<Move> {
+    pub fn probe(&self, ply: u16, index: usize) -> Option<Move> {
         self.moves[ply as usize][index]
     }
 
-    pub fn is_killer(&self, ply: u8, mv: &Move) -> bool {
+    pub fn is_killer(&self, ply: u16, mv: &Move) -> bool {
         self.moves[ply as usize].iter().any(|k| k.is_some_and(|k| *mv == k))
     }
 
-    pub fn store(&mut self, ply: u8, mv: &Move) {
+    pub fn store(&mut self, ply: u16, mv: &Move) {
         let moves = &mut self.moves[ply as usize];
 
         if moves[0].is_none() || !mv.equals(&moves[0].unwrap()) {
@@ -0,0 +1,347 @@
+use super::{
+    MAX_DEPTH,
+    report::{Report, Reporter},
+    stopper::Stopper,
+};
+use crate::eval::EVAL_MATE;
+use crate::movegen::{Move, MoveList, generate_legal_moves, is_in_check};
+use crate::position::Position;
+use std::collections::HashMap;
+
+// Keep the mate score within reach of `Report::moves_until_mate`.
+pub const MAX_MATE_MOVES: u8 = MAX_DEPTH.div_ceil(2);
+
+const MAX_CACHE_ENTRIES: usize = 1 << 22;
+
+// A proof-oriented search for a forced mate. Unlike alpha-beta there's no
+// evaluation and no pruning: the attacker looks for any move that mates by
+// force and the defender must refute every one of them, so the result is an
+// exhaustive proof within the given number of moves.
+struct MateSearch<'a> {
+    report: Report,
+    stopper: &'a Stopper<'a>,
+    // Positions (attacker to move) known to mate within the given number of
+    // moves along with the mating move, and positions known not to. They're
+    // keyed on the history as well since a repetition or the fifty-move rule
+    // can stop a mate that would otherwise go through.
+    proofs: HashMap<u64, (u8, Move)>,
+    disproofs: HashMap<u64, u8>,
+    stopped: bool,
+}
+
+pub fn search(pos: &mut Position, reporter: &impl Reporter, stopper: &Stopper, moves: u8) -> bool {
+    let mut ms = MateSearch {
+        report: Report::new(),
+        stopper,
+        proofs: HashMap::new(),
+        disproofs: HashMap::new(),
+        stopped: false,
+    };
+
+    // Search for shorter mates first so that the first proof is the shortest.
+    for n in 1..=moves.min(MAX_MATE_MOVES) {
+        let mated = ms.attack(pos, n).is_some();
+
+        if ms.stopped {
+            break;
+        }
+
+        ms.report.depth = n * 2 - 1;
+
+        if mated {
+            let pv = ms.principal_variation(pos, n);
+            ms.report.pv = Some((pv, EVAL_MATE - ms.report.depth as i32));
+            reporter.send(&ms.report);
+            return true;
+        }
+
+        reporter.send(&ms.report);
+    }
+
+    false
+}
+
+impl MateSearch<'_> {
+    // Find a move that forces mate within `n` moves for the side to move.
+    fn attack(&mut self, pos: &mut Position, n: u8) -> Option<Move> {
+        // The defender can claim the draw rather than wait to be mated.
+        if pos.is_fifty_move_draw() || pos.is_repetition_draw(0) {
+            return None;
+        }
+
+        let key = pos.key_with_history();
+
+        if let Some(&(proven, mv)) = self.proofs.get(&key)
+            && proven <= n
+        {
+            return Some(mv);
+        }
+
+        if let Some(&disproven) = self.disproofs.get(&key)
+            && disproven >= n
+        {
+            return None;
+        }
+
+        self.report.nodes += 1;
+
+        if self.stopped || self.stopper.should_stop(&self.report) {
+            self.stopped = true;
+            return None;
+        }
+
+        for mv in order_attacking_moves(pos, n) {
+            pos.do_move(&mv);
+            let is_mate = self.defend(pos, n);
+            pos.undo_move(&mv);
+
+            if self.stopped {
+                return None;
+            }
+
+            if is_mate {
+                self.make_room();
+                self.proofs.insert(key, (n, mv));
+                return Some(mv);
+            }
+        }
+
+        self.make_room();
+        self.disproofs.insert(key, n);
+
+        None
+    }
+
+    // Check that every defence leads to mate, with the attacker having just
+    // played the first of their `n` moves.
+    fn defend(&mut self, pos: &mut Position, n: u8) -> bool {
+        self.report.nodes += 1;
+
+        let moves = generate_legal_moves(pos);
+
+        if moves.is_empty() {
+            return is_in_check(pos.colour_to_move, &pos.board);
+        }
+
+        // Checkmate takes precedence over the fifty-move rule.
+        if n == 1 || pos.is_fifty_move_draw() || pos.is_repetition_draw(0) {
+            return false;
+        }
+
+        for mv in moves {
+            pos.do_move(&mv);
+            let is_mate = self.attack(pos, n - 1).is_some();
+            pos.undo_move(&mv);
+
+            if !is_mate {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    // Follow the proof, with the defender choosing the reply that delays mate
+    // for as long as possible.
+    fn principal_variation(&mut self, pos: &Position, mut n: u8) -> MoveList {
+        let mut pos = pos.clone();
+        let mut pv = MoveList::new();
+
+        while let Some(mv) = self.attack(&mut pos, n) {
+            pv.push(mv);
+            pos.do_move(&mv);
+
+            let mut longest_defence: Option<(u8, Move)> = None;
+
+            for reply in generate_legal_moves(&mut pos) {
+                pos.do_move(&reply);
+                let moves_until_mate = (1..n).find(|&m| self.attack(&mut pos, m).is_some()).unwrap_or(n - 1);
+                pos.undo_move(&reply);
+
+                if longest_defence.is_none_or(|(longest, _)| moves_until_mate > longest) {
+                    longest_defence = Some((moves_until_mate, reply));
+                }
+            }
+
+            let Some((moves_until_mate, reply)) = longest_defence else {
+                break;
+            };
+
+            pv.push(reply);
+            pos.do_move(&reply);
+            n = moves_until_mate;
+        }
+
+        pv
+    }
+
+    fn make_room(&mut self) {
+        if self.proofs.len() + self.disproofs.len() >= MAX_CACHE_ENTRIES {
+            self.proofs.clear();
+            self.disproofs.clear();
+        }
+    }
+}
+
+// A mate must be delivered with check, so only checks are considered for the
+// final move. Otherwise try checks first, then captures, since these are the
+// most forcing and most likely to lead to a quick proof.
+fn order_attacking_moves(pos: &mut Position, n: u8) -> MoveList {
+    let mut checks = MoveList::new();
+    let mut captures = MoveList::new();
+    let mut quiets = MoveList::new();
+
+    for mv in generate_legal_moves(pos) {
+        pos.do_move(&mv);
+        let gives_check = is_in_check(pos.colour_to_move, &pos.board);
+        pos.undo_move(&mv);
+
+        if gives_check {
+            checks.push(mv);
+        } else if n == 1 {
+            continue;
+        } else if mv.captured_piece.is_some() {
+            captures.push(mv);
+        } else {
+            quiets.push(mv);
+        }
+    }
+
+    checks.extend(captures);
+    checks.extend(quiets);
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piece::Piece;
+    use crate::square::Square;
+    use crate::testing::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn find_mate_in_one() {
+        let mut pos = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let reporter = TestReporter::new();
+
+        assert!(search(&mut pos, &reporter, &Stopper::new(), 1));
+
+        assert_eq!(reporter.moves_until_mate(), Some(1));
+        assert_eq!(reporter.pv(), vec![make_move(Piece::WR, Square::A1, Square::A8, None)]);
+    }
+
+    #[test]
+    fn find_the_shortest_mate() {
+        let mut pos = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let reporter = TestReporter::new();
+
+        assert!(search(&mut pos, &reporter, &Stopper::new(), 3));
+
+        assert_eq!(reporter.moves_until_mate(), Some(1));
+    }
+
+    #[test]
+    fn find_mate_in_two_with_a_quiet_first_move() {
+        // 1. Kb6 forces 1... Kb8 2. Rd8#
+        let mut pos = parse_fen("k7/8/2K5/8/8/8/8/3R4 w - - 0 1");
+        let reporter = TestReporter::new();
+
+        assert!(!search(&mut pos.clone(), &reporter, &Stopper::new(), 1));
+        assert!(search(&mut pos, &reporter, &Stopper::new(), 2));
+
+        assert_eq!(reporter.moves_until_mate(), Some(3));
+        assert_eq!(reporter.pv().len(), 3);
+        assert_is_mate(&mut pos, &reporter.pv());
+    }
+
+    #[test]
+    fn find_mate_in_three() {
+        let mut pos = parse_fen("r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1");
+        let reporter = TestReporter::new();
+
+        assert!(search(&mut pos, &reporter, &Stopper::new(), 3));
+
+        assert_eq!(reporter.moves_until_mate(), Some(5));
+        assert_is_mate(&mut pos, &reporter.pv());
+    }
+
+    #[test]
+    fn stalemate_is_not_mate() {
+        let mut pos = parse_fen("k7/8/1Q6/8/8/8/8/7K b - - 0 1");
+        let reporter = TestReporter::new();
+
+        assert!(!search(&mut pos, &reporter, &Stopper::new(), 2));
+        assert!(reporter.pv().is_empty());
+    }
+
+    #[test]
+    fn the_fifty_move_rule_can_stop_a_mate() {
+        // The quiet 1. Kb6 lets Black claim the draw before 2. Rd8#, but a
+        // mate on the hundredth half-move still counts.
+        let mut quiet_first_move = parse_fen("k7/8/2K5/8/8/8/8/3R4 w - - 99 80");
+        let mut mate_in_one = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 99 80");
+        let reporter = TestReporter::new();
+
+        assert!(!search(&mut quiet_first_move, &reporter, &Stopper::new(), 2));
+        assert!(search(&mut mate_in_one, &reporter, &Stopper::new(), 1));
+    }
+
+    #[test]
+    fn a_repetition_can_stop_a_mate() {
+        // Shuffling the kings back and forth brings the position after 1. Kb6
+        // round for the third time, so Black can claim the draw.
+        let mut pos = parse_fen("k7/8/2K5/8/8/8/8/3R4 w - - 0 1");
+        let mut repeated = pos.clone();
+        let reporter = TestReporter::new();
+
+        for _ in 0..2 {
+            for mv in [
+                make_move(Piece::WK, Square::C6, Square::B6, None),
+                make_move(Piece::BK, Square::A8, Square::B8, None),
+                make_move(Piece::WK, Square::B6, Square::C6, None),
+                make_move(Piece::BK, Square::B8, Square::A8, None),
+            ] {
+                repeated.do_move(&mv);
+            }
+        }
+
+        assert!(search(&mut pos, &reporter, &Stopper::new(), 2));
+        assert!(!search(&mut repeated, &reporter, &Stopper::new(), 2));
+    }
+
+    fn assert_is_mate(pos: &mut Position, pv: &[Move]) {
+        for mv in pv {
+            pos.do_move(mv);
+        }
+
+        assert!(generate_legal_moves(pos).is_empty());
+        assert!(is_in_check(pos.colour_to_move, &pos.board));
+    }
+
+    struct TestReporter {
+        pv: RefCell<Option<(MoveList, i32)>>,
+    }
+
+    impl TestReporter {
+        fn new() -> Self {
+            Self { pv: RefCell::new(None) }
+        }
+
+        fn pv(&self) -> Vec<Move> {
+            self.pv.borrow().as_ref().map_or(vec![], |(moves, _)| moves.to_vec())
+        }
+
+        fn moves_until_mate(&self) -> Option<i32> {
+            self.pv.borrow().as_ref().map(|(_, eval)| EVAL_MATE - eval)
+        }
+    }
+
+    impl Reporter for TestReporter {
+        fn send(&self, report: &Report) {
+            if let Some(pv) = &report.pv {
+                *self.pv.borrow_mut() = Some(pv.clone());
+            }
+        }
+    }
+}
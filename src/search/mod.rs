@@ -1,18 +1,28 @@
 use self::{
     history::HistoryTable,
     killers::KillerMoves,
+    params::SearchParams,
     pv::PvTable,
     report::{Report, Reporter},
+    stats::SearchStats,
     stopper::Stopper,
-    tt::TranspositionTable,
+    tree::SearchTree,
+    tt::{Bound, TranspositionTable},
 };
+use crate::colour::Colour;
 use crate::eval::*;
-use crate::movegen::{Move, MoveList, generate_all_moves, is_in_check};
+use crate::movegen::{Move, MoveList, generate_all_moves, generate_legal_moves, is_in_check};
 use crate::position::Position;
 
+pub mod mate;
+pub mod params;
+pub mod pns;
 pub mod report;
+pub mod skill;
+pub mod stats;
 pub mod stopper;
 pub mod time;
+pub mod tree;
 pub mod tt;
 
 mod alphabeta;
@@ -24,13 +34,90 @@ mod quiescence;
 
 pub const MAX_DEPTH: u8 = u8::MAX;
 
-struct SearchState<'a> {
+// Check extensions and quiescence take the search beyond the nominal depth, so
+// leave plenty of room for them.
+pub const MAX_PLY: u16 = MAX_DEPTH as u16 * 2;
+
+struct SearchState<'a, E: Evaluator> {
     pub report: Report,
     pub stopper: &'a Stopper<'a>,
     pub tt: &'a mut TranspositionTable,
     pub killers: KillerMoves,
     pub history: HistoryTable,
     pub pv: PvTable,
+    pub root_pv: Option<(MoveList, i32)>,
+    pub root_colour: Colour,
+    pub excluded_root_moves: MoveList,
+    pub params: &'a SearchParams,
+    pub root_move_nodes: Vec<(Move, u64)>,
+    pub evaluator: &'a mut E,
+    pub reporter: &'a dyn Reporter,
+    pub tree: Option<SearchTree>,
+}
+
+impl<E: Evaluator> SearchState<'_, E> {
+    // Make/unmake moves through these so that the evaluator is kept in sync.
+    #[inline(always)]
+    pub fn do_move(&mut self, pos: &mut Position, mv: &Move) {
+        self.evaluator.do_move(mv);
+        pos.do_move(mv);
+    }
+
+    #[inline(always)]
+    pub fn undo_move(&mut self, pos: &mut Position, mv: &Move) {
+        self.evaluator.undo_move(mv);
+        pos.undo_move(mv);
+    }
+
+    #[inline(always)]
+    pub fn is_excluded(&self, ply: u16, mv: &Move) -> bool {
+        ply == 0 && self.excluded_root_moves.iter().any(|excluded| excluded.equals(mv))
+    }
+
+    pub fn add_root_move_nodes(&mut self, mv: &Move, nodes: u64) {
+        if let Some((_, total)) = self
+            .root_move_nodes
+            .iter_mut()
+            .find(|(root_move, _)| root_move.equals(mv))
+        {
+            *total += nodes;
+        }
+    }
+
+    pub fn root_move_nodes(&self, mv: &Move) -> u64 {
+        self.root_move_nodes
+            .iter()
+            .find(|(root_move, _)| root_move.equals(mv))
+            .map_or(0, |(_, nodes)| *nodes)
+    }
+
+    #[inline(always)]
+    pub fn count(&mut self, update: impl FnOnce(&mut SearchStats)) {
+        if self.params.stats {
+            update(&mut self.report.stats);
+        }
+    }
+
+    #[inline(always)]
+    pub fn record(&mut self, update: impl FnOnce(&mut SearchTree)) {
+        if let Some(tree) = &mut self.tree {
+            update(tree);
+        }
+    }
+
+    // Draws are scored from the point of view of the side to move at the root
+    // so that contempt makes it avoid them. A little noise from the node count
+    // stops the search from blindly steering into or away from repetitions.
+    #[inline(always)]
+    pub fn draw_eval(&self, pos: &Position) -> i32 {
+        let noise = 1 - (self.report.nodes & 2) as i32;
+
+        if pos.colour_to_move == self.root_colour {
+            EVAL_DRAW - self.params.contempt + noise
+        } else {
+            EVAL_DRAW + self.params.contempt + noise
+        }
+    }
 }
 
 // Aspiration window tuning
@@ -39,12 +126,24 @@ const ASP_BASE_DELTA: i32 = 25; // Quarter pawn
 const ASP_EXPANSION_FACTOR: i32 = 2;
 const ASP_MAX_RETRIES: u8 = 3;
 
-#[rustfmt::skip]
 pub fn search(
     pos: &mut Position,
     tt: &mut TranspositionTable,
     reporter: &impl Reporter,
     stopper: &Stopper,
+    params: &SearchParams,
+) {
+    search_with_evaluator(pos, tt, reporter, stopper, params, &mut HandcraftedEval);
+}
+
+#[rustfmt::skip]
+pub fn search_with_evaluator(
+    pos: &mut Position,
+    tt: &mut TranspositionTable,
+    reporter: &impl Reporter,
+    stopper: &Stopper,
+    params: &SearchParams,
+    evaluator: &mut impl Evaluator,
 ) {
     tt.age();
 
@@ -62,6 +161,14 @@ pub fn search(
         killers: KillerMoves::new(),
         history: HistoryTable::new(),
         pv: PvTable::new(),
+        root_pv: None,
+        root_colour: pos.colour_to_move,
+        excluded_root_moves: MoveList::new(),
+        params,
+        root_move_nodes: generate_legal_moves(pos).iter().map(|mv| (*mv, 0)).collect(),
+        evaluator,
+        reporter,
+        tree: params.tree.clone().map(SearchTree::new),
     };
 
     let mut last_eval: i32 = 0;
@@ -71,6 +178,7 @@ pub fn search(
         // Bypass aspiration search for shallow depths or near-mate situations
         let do_asp = depth >= ASP_MIN_DEPTH && last_eval.abs() < EVAL_MATE_THRESHOLD;
         let (mut delta_low, mut delta_high) = (ASP_BASE_DELTA, ASP_BASE_DELTA);
+        ss.root_pv = None;
 
         let (mut alpha, mut beta) = if do_asp {
             (
@@ -91,6 +199,8 @@ pub fn search(
                     break eval;
                 }
 
+                report_bound(&ss, depth, eval, if eval <= alpha { Bound::Upper } else { Bound::Lower });
+
                 asp_retries += 1;
                 if asp_retries > ASP_MAX_RETRIES {
                     alpha = EVAL_MIN;
@@ -109,12 +219,31 @@ pub fn search(
         };
 
         if stopper.should_stop(&ss.report) {
+            // Don't throw away an unfinished iteration if it has already found
+            // a move with an exact score, since that's searched deeper than
+            // the previous best.
+            if let Some(root_pv) = ss.root_pv.take() {
+                ss.report.pv = Some(sanitise_pv(pos.clone(), ss.tt, root_pv, -params.contempt));
+                reporter.send(&ss.report);
+            }
+
             break;
         }
 
+        if params.candidates > 1
+            && let Some(best_move) = ss.pv.root().first().copied()
+            && let Some(candidates) = search_candidates(&mut ss, pos, depth, (best_move, last_eval))
+        {
+            ss.report.candidates = candidates;
+        }
+
         ss.report.depth = depth;
-        ss.report.pv = Some(sanitise_pv(pos.clone(), (ss.pv.root().clone(), last_eval)));
+        ss.report.pv = Some(sanitise_pv(pos.clone(), ss.tt, (ss.pv.root().clone(), last_eval), -params.contempt));
         ss.report.tt_usage = ss.tt.usage();
+        ss.report.best_move_nodes = match ss.report.best_move() {
+            Some(mv) => ss.root_move_nodes(&mv),
+            None => 0,
+        };
 
         reporter.send(&ss.report);
 
@@ -122,6 +251,72 @@ pub fn search(
             break;
         }
     }
+
+    if let Some(tree) = &ss.tree {
+        reporter.send_tree(tree);
+    }
+}
+
+// Let the reporter know about aspiration window failures so that analysis
+// doesn't go quiet during re-searches. A fail high has a partial PV from the
+// move that failed high, but a fail low has nothing better than the previous
+// iteration's PV.
+fn report_bound<E: Evaluator>(ss: &SearchState<E>, depth: u8, eval: i32, bound: Bound) {
+    let moves = match bound {
+        Bound::Lower => ss.pv.root().clone(),
+        _ => ss
+            .report
+            .pv
+            .as_ref()
+            .map(|(moves, _)| moves.clone())
+            .unwrap_or_default(),
+    };
+
+    let mut report = ss.report.clone();
+    report.depth = depth;
+    report.pv = Some((moves, eval));
+    report.bound = bound;
+
+    ss.reporter.send(&report);
+}
+
+// Score the next best root moves by searching the root again with the moves
+// already found excluded, in the style of MultiPV. Gives up if the search is
+// stopped part way, leaving the candidates from the previous iteration.
+fn search_candidates<E: Evaluator>(
+    ss: &mut SearchState<E>,
+    pos: &mut Position,
+    depth: u8,
+    best: (Move, i32),
+) -> Option<Vec<(Move, i32)>> {
+    let best_pv = ss.pv.root().clone();
+    let mut candidates = vec![best];
+
+    while candidates.len() < ss.params.candidates {
+        ss.excluded_root_moves = candidates.iter().map(|(mv, _)| *mv).collect();
+
+        let eval = alphabeta::search(ss, pos, depth, EVAL_MIN, EVAL_MAX, 0);
+
+        if ss.stopper.should_stop(&ss.report) {
+            candidates.clear();
+            break;
+        }
+
+        // There are no more legal moves.
+        let Some(&mv) = ss.pv.root().first() else {
+            break;
+        };
+
+        candidates.push((mv, eval));
+    }
+
+    // Put back the best line since the root entries in the PV and TT were
+    // overwritten by the searches without it.
+    ss.excluded_root_moves.clear();
+    ss.pv.restore_root(best_pv);
+    ss.tt.store(pos.key, depth, best.1, Bound::Exact, Some(best.0));
+
+    if candidates.is_empty() { None } else { Some(candidates) }
 }
 
 fn get_forced_move(pos: &mut Position) -> Option<Move> {
@@ -147,25 +342,58 @@ fn get_forced_move(pos: &mut Position) -> Option<Move> {
     forced_move
 }
 
-fn sanitise_pv(mut pos: Position, (moves, eval): (MoveList, i32)) -> (MoveList, i32) {
+// Trim the PV at the first draw, otherwise complete it from the TT. The
+// triangular PV stops short wherever the line ended in a TT cutoff, pruning or
+// quiescence, but following the TT moves from the end of it usually recovers
+// the rest of the line.
+fn sanitise_pv(
+    mut pos: Position,
+    tt: &TranspositionTable,
+    (mut moves, eval): (MoveList, i32),
+    draw_eval: i32,
+) -> (MoveList, i32) {
+    let mut keys = vec![pos.key];
+
     for (index, mv) in moves.iter().enumerate() {
         pos.do_move(mv);
+        keys.push(pos.key);
 
         if pos.is_fifty_move_draw() || pos.is_repetition_draw(0) {
-            return (MoveList::from_slice(&moves[..=index]), EVAL_DRAW);
+            return (MoveList::from_slice(&moves[..=index]), draw_eval);
         }
     }
 
+    // TT moves may be from a different position with the same index, so make
+    // sure each is legal, and stop before the line goes round in circles.
+    while moves.len() < MAX_PLY as usize
+        && let Some(tt_move) = tt.probe(pos.key).and_then(|entry| entry.mv)
+        && let Some(mv) = generate_legal_moves(&mut pos).into_iter().find(|mv| *mv == tt_move)
+    {
+        pos.do_move(&mv);
+
+        if keys.contains(&pos.key) {
+            break;
+        }
+
+        keys.push(pos.key);
+        moves.push(mv);
+    }
+
     (moves, eval)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::eval::material::MaterialEval;
     use crate::piece::Piece;
+    use crate::position::START_POS_FEN;
     use crate::square::Square;
     use crate::testing::*;
-    use std::cell::Cell;
+    use crate::uci::r#move::UciMove;
+    use std::cell::{Cell, RefCell};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tree::{Cutoff, NodeMove, TreeFilter, TreeNode};
 
     #[test]
     fn report_forced_moves_without_searching() {
@@ -175,7 +403,7 @@ mod tests {
         let mut stopper = Stopper::new();
         stopper.at_depth(Some(1));
 
-        search(&mut pos, &mut tt, &reporter, &stopper);
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
 
         assert_eq!(reporter.nodes(), 0);
         assert_eq!(
@@ -184,9 +412,299 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_with_a_custom_evaluator() {
+        let mut pos = parse_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+        let mut tt = TranspositionTable::new(1);
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(4));
+
+        // The material evaluator checks that it's kept in sync on every eval.
+        let mut evaluator = MaterialEval::new(&pos);
+        search_with_evaluator(
+            &mut pos,
+            &mut tt,
+            &reporter,
+            &stopper,
+            &SearchParams::default(),
+            &mut evaluator,
+        );
+
+        assert_eq!(
+            reporter.best_move(),
+            Some(make_move(Piece::WR, Square::D1, Square::D5, Some(Piece::BQ)))
+        );
+    }
+
+    #[test]
+    fn report_each_root_move_as_it_is_searched() {
+        let mut pos = parse_fen(START_POS_FEN);
+        let mut tt = TranspositionTable::new(1);
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(1));
+
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
+
+        assert_eq!(reporter.last_move_number(), 20);
+    }
+
+    #[test]
+    fn report_aspiration_window_failures_as_bounds() {
+        let mut pos = parse_fen("r1b1kb1r/3q1ppp/pBp1pn2/8/Np3P2/5B2/PPP3PP/R2Q1RK1 w kq - 0 1");
+        let mut tt = TranspositionTable::new(1);
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(5));
+
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
+
+        assert!(reporter.bounds() > 0);
+    }
+
+    #[test]
+    fn use_the_best_move_from_an_unfinished_iteration() {
+        let mut pos = parse_fen(START_POS_FEN);
+        let mut tt = TranspositionTable::new(1);
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_signal(&reporter.stop_signal);
+
+        // The 4th iteration prefers d4 but the 5th prefers e4, so stop as soon
+        // as that's been searched and another root move is tried.
+        let e4 = make_move(Piece::WP, Square::E2, Square::E4, None);
+        reporter.stop_after.set(Some((4, e4)));
+
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
+
+        assert_eq!(reporter.depth(), 4);
+        assert_eq!(reporter.best_move(), Some(e4));
+    }
+
+    #[test]
+    fn report_the_shortest_mate_when_reusing_the_tt() {
+        let mut pos = parse_fen("r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1");
+        let mut tt = TranspositionTable::new(1);
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(8));
+
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
+        assert_eq!(reporter.moves_until_mate(), Some(5));
+
+        // Mate scores stored at other plies must be adjusted to this root.
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
+        assert_eq!(reporter.moves_until_mate(), Some(5));
+
+        pos.do_move(&reporter.best_move().unwrap());
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
+        assert_eq!(reporter.moves_until_mate(), Some(4));
+
+        pos.do_move(&reporter.best_move().unwrap());
+        search(&mut pos, &mut tt, &reporter, &stopper, &SearchParams::default());
+        assert_eq!(reporter.moves_until_mate(), Some(3));
+    }
+
+    #[test]
+    fn avoid_or_seek_repetitions_with_contempt() {
+        // Black can repeat the start position for the third time with Ng8.
+        let mut pos = parse_fen(START_POS_FEN);
+        let repetition = make_move(Piece::BN, Square::F6, Square::G8, None);
+
+        for mv in [
+            make_move(Piece::WN, Square::G1, Square::F3, None),
+            make_move(Piece::BN, Square::G8, Square::F6, None),
+            make_move(Piece::WN, Square::F3, Square::G1, None),
+            repetition,
+            make_move(Piece::WN, Square::G1, Square::F3, None),
+            make_move(Piece::BN, Square::G8, Square::F6, None),
+            make_move(Piece::WN, Square::F3, Square::G1, None),
+        ] {
+            pos.do_move(&mv);
+        }
+
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(4));
+
+        let params = SearchParams {
+            contempt: -100,
+            ..Default::default()
+        };
+        search(&mut pos, &mut TranspositionTable::new(1), &reporter, &stopper, &params);
+        assert_eq!(reporter.best_move(), Some(repetition));
+        assert_eq!(reporter.eval(), Some(100));
+
+        let params = SearchParams {
+            contempt: 100,
+            ..Default::default()
+        };
+        search(&mut pos, &mut TranspositionTable::new(1), &reporter, &stopper, &params);
+        assert_ne!(reporter.best_move(), Some(repetition));
+    }
+
+    #[test]
+    fn only_collect_stats_when_asked() {
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(4));
+
+        let mut pos = Position::startpos();
+        search(
+            &mut pos,
+            &mut TranspositionTable::new(1),
+            &reporter,
+            &stopper,
+            &SearchParams::default(),
+        );
+        assert_eq!(reporter.stats().tt_probes, 0);
+        assert_eq!(reporter.stats().qnodes, 0);
+
+        let params = SearchParams {
+            stats: true,
+            ..Default::default()
+        };
+        search(&mut pos, &mut TranspositionTable::new(1), &reporter, &stopper, &params);
+        assert!(reporter.stats().tt_probes > 0);
+        assert!(reporter.stats().qnodes > 0);
+    }
+
+    #[test]
+    fn score_candidate_moves_best_first() {
+        let mut pos = parse_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(4));
+        let params = SearchParams {
+            candidates: 3,
+            ..Default::default()
+        };
+
+        search(&mut pos, &mut TranspositionTable::new(1), &reporter, &stopper, &params);
+
+        let candidates = reporter.candidates();
+        let capture = make_move(Piece::WR, Square::D1, Square::D5, Some(Piece::BQ));
+
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0].0, capture);
+        assert_eq!(reporter.best_move(), Some(capture));
+        assert!(candidates.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!(candidates[1..].iter().all(|(mv, _)| *mv != capture));
+    }
+
+    #[test]
+    fn complete_a_short_pv_from_the_tt() {
+        let pos = parse_fen(START_POS_FEN);
+        let mut tt = TranspositionTable::new(1);
+        let e4 = make_move(Piece::WP, Square::E2, Square::E4, None);
+        let e5 = make_move(Piece::BP, Square::E7, Square::E5, None);
+        let nf3 = make_move(Piece::WN, Square::G1, Square::F3, None);
+        store_line(&mut tt, pos.clone(), &[e4, e5, nf3]);
+
+        let (moves, eval) = sanitise_pv(pos, &tt, (MoveList::from_slice(&[e4]), 30), 0);
+
+        assert_eq!(moves.as_slice(), [e4, e5, nf3]);
+        assert_eq!(eval, 30);
+    }
+
+    #[test]
+    fn stop_completing_the_pv_at_a_repetition() {
+        let pos = parse_fen(START_POS_FEN);
+        let mut tt = TranspositionTable::new(1);
+        let nf3 = make_move(Piece::WN, Square::G1, Square::F3, None);
+        let nf6 = make_move(Piece::BN, Square::G8, Square::F6, None);
+        let ng1 = make_move(Piece::WN, Square::F3, Square::G1, None);
+        let ng8 = make_move(Piece::BN, Square::F6, Square::G8, None);
+        store_line(&mut tt, pos.clone(), &[nf3, nf6, ng1, ng8]);
+
+        let (moves, _) = sanitise_pv(pos, &tt, (MoveList::from_slice(&[nf3]), 0), 0);
+
+        assert_eq!(moves.as_slice(), [nf3, nf6, ng1]);
+    }
+
+    #[test]
+    fn stop_completing_the_pv_at_an_illegal_tt_move() {
+        let pos = parse_fen(START_POS_FEN);
+        let mut tt = TranspositionTable::new(1);
+        let e4 = make_move(Piece::WP, Square::E2, Square::E4, None);
+        let d4 = make_move(Piece::WP, Square::D2, Square::D4, None);
+        store_line(&mut tt, pos.clone(), &[e4, d4]);
+
+        let (moves, _) = sanitise_pv(pos, &tt, (MoveList::from_slice(&[e4]), 0), 0);
+
+        assert_eq!(moves.as_slice(), [e4]);
+    }
+
+    #[test]
+    fn record_the_search_tree() {
+        let mut pos = parse_fen(START_POS_FEN);
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(3));
+        let params = SearchParams {
+            tree: Some(TreeFilter::default()),
+            ..Default::default()
+        };
+
+        search(&mut pos, &mut TranspositionTable::new(1), &reporter, &stopper, &params);
+
+        let tree = reporter.tree();
+        let roots: Vec<_> = tree.iter().filter(|node| node.parent.is_none()).collect();
+
+        // One root per iteration since aspiration windows start at depth 4.
+        assert_eq!(roots.len(), 3);
+        assert!(roots.iter().all(|root| root.mv == NodeMove::Root));
+        assert!(
+            tree.iter()
+                .filter_map(|node| node.parent.map(|parent| (node, &tree[parent])))
+                .all(|(node, parent)| node.ply == parent.ply + 1)
+        );
+        assert!(tree.iter().any(|node| node.cutoff == Some(Cutoff::Beta)));
+    }
+
+    #[test]
+    fn record_the_search_tree_for_one_root_move() {
+        let mut pos = parse_fen(START_POS_FEN);
+        let reporter = TestReporter::new();
+        let mut stopper = Stopper::new();
+        stopper.at_depth(Some(4));
+        let d4 = UciMove::from(make_move(Piece::WP, Square::D2, Square::D4, None));
+        let params = SearchParams {
+            tree: Some(TreeFilter {
+                max_ply: Some(2),
+                root_move: Some(d4),
+            }),
+            ..Default::default()
+        };
+
+        search(&mut pos, &mut TranspositionTable::new(1), &reporter, &stopper, &params);
+
+        let tree = reporter.tree();
+
+        assert!(tree.iter().any(|node| node.ply == 2));
+        assert!(tree.iter().all(|node| node.ply <= 2));
+        assert!(
+            tree.iter()
+                .filter(|node| node.ply == 1)
+                .all(|node| node.mv == NodeMove::Move(d4))
+        );
+    }
+
     struct TestReporter {
         nodes: Cell<u128>,
         best_move: Cell<Option<Move>>,
+        moves_until_mate: Cell<Option<u16>>,
+        eval: Cell<Option<i32>>,
+        candidates: RefCell<Vec<(Move, i32)>>,
+        last_move_number: Cell<u8>,
+        bounds: Cell<u8>,
+        depth: Cell<u8>,
+        last_move: Cell<Option<Move>>,
+        stop_after: Cell<Option<(u8, Move)>>,
+        stop_signal: AtomicBool,
+        tree: RefCell<Vec<TreeNode>>,
+        stats: Cell<stats::SearchStats>,
     }
 
     impl TestReporter {
@@ -194,6 +712,17 @@ mod tests {
             Self {
                 nodes: Cell::new(0),
                 best_move: Cell::new(None),
+                moves_until_mate: Cell::new(None),
+                eval: Cell::new(None),
+                candidates: RefCell::new(vec![]),
+                last_move_number: Cell::new(0),
+                bounds: Cell::new(0),
+                depth: Cell::new(0),
+                last_move: Cell::new(None),
+                stop_after: Cell::new(None),
+                stop_signal: AtomicBool::new(false),
+                tree: RefCell::new(vec![]),
+                stats: Cell::new(stats::SearchStats::default()),
             }
         }
 
@@ -204,15 +733,82 @@ mod tests {
         pub fn best_move(&self) -> Option<Move> {
             self.best_move.get()
         }
+
+        pub fn moves_until_mate(&self) -> Option<u16> {
+            self.moves_until_mate.get()
+        }
+
+        pub fn eval(&self) -> Option<i32> {
+            self.eval.get()
+        }
+
+        pub fn candidates(&self) -> Vec<(Move, i32)> {
+            self.candidates.borrow().clone()
+        }
+
+        pub fn last_move_number(&self) -> u8 {
+            self.last_move_number.get()
+        }
+
+        pub fn depth(&self) -> u8 {
+            self.depth.get()
+        }
+
+        pub fn bounds(&self) -> u8 {
+            self.bounds.get()
+        }
+
+        pub fn tree(&self) -> Vec<TreeNode> {
+            self.tree.borrow().clone()
+        }
+
+        pub fn stats(&self) -> stats::SearchStats {
+            self.stats.get()
+        }
     }
 
     impl Reporter for TestReporter {
         fn send(&self, report: &Report) {
             self.nodes.set(report.nodes);
+            self.stats.set(report.stats);
+
+            if !matches!(report.bound, Bound::Exact) {
+                self.bounds.set(self.bounds.get() + 1);
+                return;
+            }
+
+            self.depth.set(report.depth);
+            self.moves_until_mate.set(report.moves_until_mate());
+            self.eval.set(report.eval());
+            *self.candidates.borrow_mut() = report.candidates.clone();
 
             if let Some((moves, _)) = &report.pv {
-                self.best_move.set(Some(moves[0].into()));
+                self.best_move.set(Some(moves[0]));
             }
         }
+
+        fn send_current_move(&self, report: &Report, mv: Move, move_number: u8) {
+            self.last_move_number.set(move_number);
+            let last_move = self.last_move.replace(Some(mv));
+
+            if let Some((depth, stop_after)) = self.stop_after.get()
+                && report.depth == depth
+                && last_move == Some(stop_after)
+                && mv != stop_after
+            {
+                self.stop_signal.store(true, Ordering::Relaxed);
+            }
+        }
+
+        fn send_tree(&self, tree: &SearchTree) {
+            *self.tree.borrow_mut() = tree.nodes.clone();
+        }
+    }
+
+    fn store_line(tt: &mut TranspositionTable, mut pos: Position, moves: &[Move]) {
+        for mv in moves {
+            tt.store(pos.key, 1, 0, Bound::Exact, Some(*mv));
+            pos.do_move(mv);
+        }
     }
 }
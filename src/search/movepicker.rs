@@ -18,7 +18,7 @@ pub enum MovePickerMode<'a> {
     AllMoves {
         killers: &'a KillerMoves,
         history: &'a HistoryTable,
-        ply: u8,
+        ply: u16,
     },
     NonQuiets,
 }
@@ -0,0 +1,19 @@
+use super::tree::TreeFilter;
+
+// Settings that change how the search plays, as opposed to the limits on how
+// long it searches for, which are set on the `Stopper`.
+#[derive(Clone, Default)]
+pub struct SearchParams {
+    // The centipawns by which the side to move at the root would rather not
+    // draw. A negative contempt makes it seek draws instead.
+    pub contempt: i32,
+    // The number of root moves to score, best first, for picking between when
+    // playing at reduced strength. Beyond the first, each needs another search
+    // of the root excluding the moves already found.
+    pub candidates: usize,
+    // Record the nodes visited, limited by the filter, for debugging.
+    pub tree: Option<TreeFilter>,
+    // Count how often each part of the search takes effect, for judging
+    // search changes. Off by default to keep the counting off the hot path.
+    pub stats: bool,
+}
@@ -0,0 +1,356 @@
+use crate::movegen::{Move, generate_legal_moves, is_in_check};
+use crate::piece::Piece;
+use crate::position::{Board, Position};
+
+const INFINITY: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+    Unknown,
+}
+
+pub struct Solution {
+    pub outcome: Outcome,
+    pub nodes: usize,
+    // The moves that prove the outcome. At the nodes where the side that
+    // "owns" the proof is to move there's a single move, otherwise every
+    // reply is included.
+    pub tree: Vec<SolutionNode>,
+}
+
+pub struct SolutionNode {
+    pub mv: Move,
+    pub children: Vec<SolutionNode>,
+}
+
+// Solve the position for the side to move with a proof-number search. Each
+// pass builds its own tree of at most `max_nodes` nodes; if that's exhausted
+// before the root is proven or disproven then the outcome is unknown.
+//
+// Proof-number search only answers yes/no questions, so it takes two passes
+// to distinguish a win, a draw and a loss: first "can we force mate?" and if
+// not then "can we avoid being mated?".
+pub fn solve(pos: &mut Position, max_nodes: usize) -> Solution {
+    let mut win = ProofNumberSearch::new(Target::Win, max_nodes);
+    let is_win = win.run(pos);
+    let mut nodes = win.nodes.len();
+
+    let (outcome, tree) = match is_win {
+        Some(true) => (Outcome::Win, win.solution(0, true, true)),
+        Some(false) => {
+            let mut draw = ProofNumberSearch::new(Target::Draw, max_nodes);
+            let is_draw = draw.run(pos);
+            nodes += draw.nodes.len();
+
+            match is_draw {
+                Some(true) => (Outcome::Draw, draw.solution(0, true, true)),
+                Some(false) => (Outcome::Loss, draw.solution(0, true, false)),
+                None => (Outcome::Unknown, vec![]),
+            }
+        }
+        None => (Outcome::Unknown, vec![]),
+    };
+
+    Solution { outcome, nodes, tree }
+}
+
+// What the side to move at the root is trying to prove. A draw target is
+// proven by any result that isn't a loss.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Win,
+    Draw,
+}
+
+struct Node {
+    mv: Option<Move>,
+    parent: usize,
+    first_child: usize,
+    child_count: usize,
+    proof: u32,
+    disproof: u32,
+}
+
+impl Node {
+    fn is_solved(&self) -> bool {
+        self.proof == 0 || self.disproof == 0
+    }
+}
+
+struct ProofNumberSearch {
+    target: Target,
+    max_nodes: usize,
+    nodes: Vec<Node>,
+}
+
+impl ProofNumberSearch {
+    fn new(target: Target, max_nodes: usize) -> Self {
+        Self {
+            target,
+            max_nodes,
+            nodes: Vec::new(),
+        }
+    }
+
+    // Returns whether the target was proven, or `None` if the node table
+    // filled up first.
+    fn run(&mut self, pos: &mut Position) -> Option<bool> {
+        let (proof, disproof) = self.initial_numbers(pos, 0);
+
+        self.nodes.push(Node {
+            mv: None,
+            parent: 0,
+            first_child: 0,
+            child_count: 0,
+            proof,
+            disproof,
+        });
+
+        let mut path = Vec::new();
+
+        while !self.nodes[0].is_solved() {
+            // Descend to the most-proving node, playing the moves on the way.
+            let mut index = 0;
+
+            while self.nodes[index].child_count > 0 {
+                index = self.most_proving_child(index, path.len());
+                let mv = self.nodes[index].mv.unwrap();
+                pos.do_move(&mv);
+                path.push(mv);
+            }
+
+            let is_full = !self.expand(index, pos, path.len());
+
+            // Back up the new proof numbers, returning to the root position.
+            loop {
+                self.update(index, path.len());
+
+                let Some(mv) = path.pop() else {
+                    break;
+                };
+
+                pos.undo_move(&mv);
+                index = self.nodes[index].parent;
+            }
+
+            if is_full {
+                return None;
+            }
+        }
+
+        Some(self.nodes[0].proof == 0)
+    }
+
+    // The root side is to move at even plies, where only one child needs to
+    // be proven. At odd plies every child does.
+    fn most_proving_child(&self, index: usize, ply: usize) -> usize {
+        let node = &self.nodes[index];
+        let children = node.first_child..node.first_child + node.child_count;
+
+        if ply.is_multiple_of(2) {
+            children.min_by_key(|&child| self.nodes[child].proof).unwrap()
+        } else {
+            children.min_by_key(|&child| self.nodes[child].disproof).unwrap()
+        }
+    }
+
+    fn expand(&mut self, index: usize, pos: &mut Position, ply: usize) -> bool {
+        let moves = generate_legal_moves(pos);
+
+        if self.nodes.len() + moves.len() > self.max_nodes {
+            return false;
+        }
+
+        self.nodes[index].first_child = self.nodes.len();
+        self.nodes[index].child_count = moves.len();
+
+        for mv in moves {
+            pos.do_move(&mv);
+            let (proof, disproof) = self.initial_numbers(pos, ply + 1);
+            pos.undo_move(&mv);
+
+            self.nodes.push(Node {
+                mv: Some(mv),
+                parent: index,
+                first_child: 0,
+                child_count: 0,
+                proof,
+                disproof,
+            });
+        }
+
+        true
+    }
+
+    fn update(&mut self, index: usize, ply: usize) {
+        let node = &self.nodes[index];
+
+        if node.child_count == 0 {
+            return;
+        }
+
+        let children = &self.nodes[node.first_child..node.first_child + node.child_count];
+        let min_proof = children.iter().map(|child| child.proof).min().unwrap();
+        let min_disproof = children.iter().map(|child| child.disproof).min().unwrap();
+        let sum_proof = children
+            .iter()
+            .fold(0, |sum: u32, child| sum.saturating_add(child.proof));
+        let sum_disproof = children
+            .iter()
+            .fold(0, |sum: u32, child| sum.saturating_add(child.disproof));
+
+        let node = &mut self.nodes[index];
+
+        if ply.is_multiple_of(2) {
+            (node.proof, node.disproof) = (min_proof, sum_disproof);
+        } else {
+            (node.proof, node.disproof) = (sum_proof, min_disproof);
+        }
+    }
+
+    // Terminal positions are solved immediately. Otherwise the numbers are
+    // seeded with the mobility of the side to move, since a node with fewer
+    // moves needs fewer of them refuting.
+    fn initial_numbers(&self, pos: &mut Position, ply: usize) -> (u32, u32) {
+        let is_root_side = ply.is_multiple_of(2);
+
+        let move_count = generate_legal_moves(pos).len() as u32;
+
+        // Checkmate takes precedence over the fifty-move rule.
+        if move_count == 0 {
+            return match (is_in_check(pos.colour_to_move, &pos.board), is_root_side) {
+                (true, true) => self.terminal_numbers(Outcome::Loss),
+                (true, false) => self.terminal_numbers(Outcome::Win),
+                (false, _) => self.terminal_numbers(Outcome::Draw),
+            };
+        }
+
+        if pos.is_fifty_move_draw()
+            || pos.is_repetition_draw(ply.min(u16::MAX as usize) as u16)
+            || is_insufficient_material(&pos.board)
+        {
+            return self.terminal_numbers(Outcome::Draw);
+        }
+
+        if is_root_side { (1, move_count) } else { (move_count, 1) }
+    }
+
+    fn terminal_numbers(&self, outcome: Outcome) -> (u32, u32) {
+        let is_proven = match self.target {
+            Target::Win => outcome == Outcome::Win,
+            Target::Draw => outcome != Outcome::Loss,
+        };
+
+        if is_proven { (0, INFINITY) } else { (INFINITY, 0) }
+    }
+
+    // Collect the moves that prove (or disprove) the node. The side the proof
+    // belongs to needs a single solved move, while the other side has to be
+    // answered on every reply.
+    fn solution(&self, index: usize, is_root_side: bool, is_proof: bool) -> Vec<SolutionNode> {
+        let node = &self.nodes[index];
+        let mut children = node.first_child..node.first_child + node.child_count;
+
+        let is_solved = |child: &usize| match is_proof {
+            true => self.nodes[*child].proof == 0,
+            false => self.nodes[*child].disproof == 0,
+        };
+
+        let children: Vec<usize> = if is_root_side == is_proof {
+            children.find(is_solved).into_iter().collect()
+        } else {
+            children.collect()
+        };
+
+        children
+            .into_iter()
+            .map(|child| SolutionNode {
+                mv: self.nodes[child].mv.unwrap(),
+                children: self.solution(child, !is_root_side, is_proof),
+            })
+            .collect()
+    }
+}
+
+fn is_insufficient_material(board: &Board) -> bool {
+    match board.occupancy().count_ones() {
+        2 => true,
+        3 => [Piece::WN, Piece::WB, Piece::BN, Piece::BB]
+            .iter()
+            .any(|&piece| board.count_pieces(piece) == 1),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piece::Piece;
+    use crate::square::Square;
+    use crate::testing::*;
+
+    #[test]
+    fn solve_a_mate_in_one_as_a_win() {
+        let mut pos = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+
+        let solution = solve(&mut pos, 10_000);
+
+        assert_eq!(solution.outcome, Outcome::Win);
+        assert_eq!(solution.tree.len(), 1);
+        assert_eq!(solution.tree[0].mv, make_move(Piece::WR, Square::A1, Square::A8, None));
+        assert!(solution.tree[0].children.is_empty());
+    }
+
+    #[test]
+    fn solve_a_forced_mate_as_a_win() {
+        let mut pos = parse_fen("k7/8/2K5/8/8/8/8/3R4 w - - 0 1");
+
+        let solution = solve(&mut pos, 100_000);
+
+        assert_eq!(solution.outcome, Outcome::Win);
+        assert_eq!(solution.tree.len(), 1);
+    }
+
+    #[test]
+    fn solve_being_mated_as_a_loss() {
+        // 1... Kb8 is forced and allows 2. Rd8#
+        let mut pos = parse_fen("k7/8/1K6/8/8/8/8/3R4 b - - 0 1");
+
+        let solution = solve(&mut pos, 100_000);
+
+        assert_eq!(solution.outcome, Outcome::Loss);
+        assert_eq!(solution.tree.len(), 1);
+        assert_eq!(
+            solution.tree[0].children[0].mv,
+            make_move(Piece::WR, Square::D1, Square::D8, None)
+        );
+    }
+
+    #[test]
+    fn solve_insufficient_material_as_a_draw() {
+        let mut pos = parse_fen("4k3/8/8/8/8/8/8/3NK3 w - - 0 1");
+
+        assert_eq!(solve(&mut pos, 1_000).outcome, Outcome::Draw);
+    }
+
+    #[test]
+    fn solve_stalemate_as_a_draw() {
+        // Black is stalemated.
+        let mut pos = parse_fen("k7/1R6/1K6/8/8/8/8/8 b - - 0 1");
+
+        assert_eq!(solve(&mut pos, 1_000).outcome, Outcome::Draw);
+    }
+
+    #[test]
+    fn report_unknown_when_the_node_table_is_full() {
+        let mut pos = parse_fen(crate::position::START_POS_FEN);
+
+        let solution = solve(&mut pos, 1_000);
+
+        assert_eq!(solution.outcome, Outcome::Unknown);
+        assert!(solution.nodes <= 1_000);
+    }
+}
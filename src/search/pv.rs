@@ -1,14 +1,15 @@
-use super::MAX_DEPTH;
+use super::MAX_PLY;
 use crate::movegen::{Move, MoveList};
 
+// Kept on the heap since there's a move list for every ply.
 pub struct PvTable {
-    table: [MoveList; MAX_DEPTH as usize + 1],
+    table: Vec<MoveList>,
 }
 
 impl PvTable {
     pub fn new() -> Self {
         Self {
-            table: std::array::from_fn(|_| MoveList::new()),
+            table: vec![MoveList::new(); MAX_PLY as usize + 1],
         }
     }
 
@@ -16,11 +17,15 @@ impl PvTable {
         &self.table[0]
     }
 
-    pub fn clear(&mut self, ply: u8) {
+    pub fn restore_root(&mut self, pv: MoveList) {
+        self.table[0] = pv;
+    }
+
+    pub fn clear(&mut self, ply: u16) {
         self.table[ply as usize].clear();
     }
 
-    pub fn update(&mut self, ply: u8, mv: Move) {
+    pub fn update(&mut self, ply: u16, mv: Move) {
         let (parent, child) = self.table.split_at_mut((ply + 1) as usize);
         let pv = &mut parent[ply as usize];
         pv.clear();
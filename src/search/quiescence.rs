@@ -4,10 +4,12 @@ use super::{
 };
 use crate::movegen::is_in_check;
 
-pub fn search(pos: &mut Position, mut alpha: i32, beta: i32, report: &mut Report) -> i32 {
-    report.nodes += 1;
+pub fn search<E: Evaluator>(ss: &mut SearchState<E>, pos: &mut Position, mut alpha: i32, beta: i32, ply: u16) -> i32 {
+    ss.report.nodes += 1;
+    ss.count(|stats| stats.qnodes += 1);
+    ss.report.seldepth = ss.report.seldepth.max(ply);
 
-    let eval = eval(pos);
+    let eval = ss.evaluator.eval(pos);
 
     if eval >= beta {
         return beta;
@@ -17,20 +19,24 @@ pub fn search(pos: &mut Position, mut alpha: i32, beta: i32, report: &mut Report
         alpha = eval;
     }
 
+    if ply >= MAX_PLY {
+        return alpha;
+    }
+
     let colour_to_move = pos.colour_to_move;
     let mut move_picker = MovePicker::new(pos, MovePickerMode::NonQuiets);
 
     while let Some(mv) = move_picker.pick() {
-        pos.do_move(&mv);
+        ss.do_move(pos, &mv);
 
         if is_in_check(colour_to_move, &pos.board) {
-            pos.undo_move(&mv);
+            ss.undo_move(pos, &mv);
             continue;
         }
 
-        let eval = -search(pos, -beta, -alpha, report);
+        let eval = -search(ss, pos, -beta, -alpha, ply + 1);
 
-        pos.undo_move(&mv);
+        ss.undo_move(pos, &mv);
 
         if eval >= beta {
             return beta;
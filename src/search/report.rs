@@ -1,11 +1,17 @@
-use super::*;
+use super::{stats::SearchStats, tree::SearchTree, tt::Bound, *};
 use std::time::{Duration, Instant};
 
+#[derive(Clone)]
 pub struct Report {
     pub depth: u8,
     pub nodes: u128,
     pub pv: Option<(MoveList, i32)>,
+    pub bound: Bound,
+    pub candidates: Vec<(Move, i32)>,
     pub tt_usage: usize,
+    pub best_move_nodes: u64,
+    pub seldepth: u16,
+    pub stats: SearchStats,
     started_at: Instant,
 }
 
@@ -16,7 +22,12 @@ impl Report {
             depth: 0,
             nodes: 0,
             pv: None,
+            bound: Bound::Exact,
+            candidates: vec![],
             tt_usage: 0,
+            best_move_nodes: 0,
+            seldepth: 0,
+            stats: SearchStats::default(),
             started_at: Instant::now(),
         }
     }
@@ -30,20 +41,50 @@ impl Report {
     }
 
     pub fn best_move(&self) -> Option<Move> {
-        self.pv.as_ref().map(|(moves, _)| moves[0])
+        self.pv.as_ref().and_then(|(moves, _)| moves.first().copied())
     }
 
-    pub fn moves_until_mate(&self) -> Option<u8> {
+    pub fn moves_until_mate(&self) -> Option<u16> {
         let eval = self.eval()?;
 
         if eval.abs() < EVAL_MATE_THRESHOLD || eval.abs() > EVAL_MATE {
             return None;
         }
 
-        Some((EVAL_MATE - eval.abs()) as u8)
+        Some((EVAL_MATE - eval.abs()) as u16)
     }
 }
 
 pub trait Reporter {
     fn send(&self, report: &Report);
+
+    // Called before each root move is searched.
+    fn send_current_move(&self, _report: &Report, _mv: Move, _move_number: u8) {}
+
+    // Called periodically during an iteration, between calls to `send`.
+    fn send_progress(&self, _report: &Report) {}
+
+    // Called once the search is over if it was asked to record the tree.
+    fn send_tree(&self, _tree: &SearchTree) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_moves_until_mate_beyond_the_maximum_depth() {
+        let mut report = Report::new();
+        report.pv = Some((MoveList::new(), -EVAL_MATE + 300));
+
+        assert_eq!(report.moves_until_mate(), Some(300));
+    }
+
+    #[test]
+    fn no_moves_until_mate_for_normal_evals() {
+        let mut report = Report::new();
+        report.pv = Some((MoveList::new(), 150));
+
+        assert_eq!(report.moves_until_mate(), None);
+    }
 }
@@ -0,0 +1,134 @@
+use crate::movegen::Move;
+use crate::rng::XorShift64;
+
+pub const MAX_LEVEL: u8 = 20;
+pub const MIN_ELO: u32 = 1000;
+pub const MAX_ELO: u32 = 2600;
+
+// The number of root moves scored for choosing between at reduced strength.
+pub const CANDIDATES: usize = 4;
+
+// Caps the noise, which otherwise scales with the spread of the candidates.
+const MAX_DELTA: i32 = 100;
+
+// Playing strength below the maximum, where the search is capped at a shallow
+// depth and a few thousand nodes, and a move is picked from the best few with
+// noise that grows as the level drops. Levels are fractional so that Elo can
+// be mapped onto them smoothly.
+pub struct Skill {
+    level: f32,
+}
+
+impl Skill {
+    pub fn from_level(level: u8) -> Option<Self> {
+        if level >= MAX_LEVEL {
+            return None;
+        }
+
+        Some(Self { level: level as f32 })
+    }
+
+    // A rough calibration: each level is worth about 80 Elo against club
+    // players, with level 0 playing at around 1000.
+    pub fn from_elo(elo: u32) -> Option<Self> {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+
+        if elo >= MAX_ELO {
+            return None;
+        }
+
+        let level = (elo - MIN_ELO) as f32 * MAX_LEVEL as f32 / (MAX_ELO - MIN_ELO) as f32;
+
+        Some(Self { level })
+    }
+
+    pub fn depth(&self) -> u8 {
+        1 + self.level as u8
+    }
+
+    pub fn nodes(&self) -> u128 {
+        let level = self.level as u128 + 1;
+        200 * level * level
+    }
+
+    // Weaker moves are pushed up by an amount that grows with how much worse
+    // they are and how weak the level is, plus some noise, so that lower
+    // levels more often play a move which isn't the best.
+    pub fn pick_move(&self, candidates: &[(Move, i32)], rng: &mut XorShift64) -> Option<Move> {
+        let &(_, top) = candidates.first()?;
+        let &(_, bottom) = candidates.last()?;
+
+        let delta = (top - bottom).min(MAX_DELTA);
+        let weakness = (120.0 - 2.0 * self.level) as i32;
+        let mut best: Option<(Move, i32)> = None;
+
+        for &(mv, eval) in candidates {
+            let noise = (rng.next().unwrap() % weakness as u64) as i32;
+            let push = (weakness * (top - eval) + delta * noise) / 128;
+
+            if best.is_none_or(|(_, best_eval)| eval + push >= best_eval) {
+                best = Some((mv, eval + push));
+            }
+        }
+
+        best.map(|(mv, _)| mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piece::Piece;
+    use crate::square::Square;
+    use crate::testing::*;
+
+    #[test]
+    fn no_skill_at_the_maximum_level() {
+        assert!(Skill::from_level(MAX_LEVEL).is_none());
+        assert!(Skill::from_elo(MAX_ELO).is_none());
+        assert!(Skill::from_level(MAX_LEVEL - 1).is_some());
+    }
+
+    #[test]
+    fn lower_levels_search_less() {
+        let weak = Skill::from_level(0).unwrap();
+        let strong = Skill::from_level(10).unwrap();
+
+        assert!(weak.depth() < strong.depth());
+        assert!(weak.nodes() < strong.nodes());
+    }
+
+    #[test]
+    fn map_elo_onto_levels() {
+        assert_eq!(Skill::from_elo(MIN_ELO).unwrap().depth(), 1);
+        assert_eq!(Skill::from_elo(0).unwrap().depth(), 1);
+        assert_eq!(Skill::from_elo(1800).unwrap().depth(), 11);
+    }
+
+    #[test]
+    fn never_pick_a_move_that_is_much_worse_at_higher_levels() {
+        let skill = Skill::from_level(10).unwrap();
+        let mut rng = XorShift64::new(1);
+        let best = make_move(Piece::WQ, Square::D1, Square::D8, Some(Piece::BQ));
+        let blunder = make_move(Piece::WP, Square::A2, Square::A3, None);
+
+        for _ in 0..100 {
+            let candidates = [(best, 900), (blunder, 0)];
+            assert_eq!(skill.pick_move(&candidates, &mut rng), Some(best));
+        }
+    }
+
+    #[test]
+    fn sometimes_pick_a_slightly_worse_move_at_low_levels() {
+        let skill = Skill::from_level(0).unwrap();
+        let mut rng = XorShift64::new(1);
+        let best = make_move(Piece::WP, Square::E2, Square::E4, None);
+        let other = make_move(Piece::WP, Square::D2, Square::D4, None);
+
+        let picks = (0..100)
+            .filter(|_| skill.pick_move(&[(best, 30), (other, 20)], &mut rng) == Some(other))
+            .count();
+
+        assert!(picks > 0);
+    }
+}
@@ -0,0 +1,151 @@
+use super::tt::Bound;
+use std::ops::AddAssign;
+
+// Counters for judging the effect of search changes. These are only collected
+// when the search params ask for them.
+#[derive(Clone, Copy, Default)]
+pub struct SearchStats {
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub tt_cutoffs: [u64; 3],
+    pub null_move_tries: u64,
+    pub null_move_cutoffs: u64,
+    pub lmr_searches: u64,
+    pub lmr_re_searches: u64,
+    pub rfp_prunes: u64,
+    pub futility_prunes: u64,
+    pub lmp_prunes: u64,
+    pub fail_highs: u64,
+    pub first_move_fail_highs: u64,
+    pub qnodes: u64,
+}
+
+impl SearchStats {
+    pub fn tt_cutoff(&mut self, bound: &Bound) {
+        self.tt_cutoffs[bound_index(bound)] += 1;
+    }
+
+    pub fn fail_high(&mut self, move_number: u8) {
+        self.fail_highs += 1;
+
+        if move_number == 1 {
+            self.first_move_fail_highs += 1;
+        }
+    }
+
+    pub fn summary(&self, nodes: u64) -> Vec<String> {
+        let [exact, lower, upper] = self.tt_cutoffs;
+
+        vec![
+            format!(
+                "tt hits {} cutoffs exact {exact} lower {lower} upper {upper}",
+                ratio(self.tt_hits, self.tt_probes)
+            ),
+            format!(
+                "null move cutoffs {}",
+                ratio(self.null_move_cutoffs, self.null_move_tries)
+            ),
+            format!("lmr re-searches {}", ratio(self.lmr_re_searches, self.lmr_searches)),
+            format!(
+                "prunes rfp {} futility {} lmp {}",
+                self.rfp_prunes, self.futility_prunes, self.lmp_prunes
+            ),
+            format!(
+                "first move fail highs {}",
+                ratio(self.first_move_fail_highs, self.fail_highs)
+            ),
+            format!("quiescence nodes {}", ratio(self.qnodes, nodes)),
+        ]
+    }
+}
+
+impl AddAssign for SearchStats {
+    fn add_assign(&mut self, other: Self) {
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+        for (cutoffs, other_cutoffs) in self.tt_cutoffs.iter_mut().zip(other.tt_cutoffs) {
+            *cutoffs += other_cutoffs;
+        }
+        self.null_move_tries += other.null_move_tries;
+        self.null_move_cutoffs += other.null_move_cutoffs;
+        self.lmr_searches += other.lmr_searches;
+        self.lmr_re_searches += other.lmr_re_searches;
+        self.rfp_prunes += other.rfp_prunes;
+        self.futility_prunes += other.futility_prunes;
+        self.lmp_prunes += other.lmp_prunes;
+        self.fail_highs += other.fail_highs;
+        self.first_move_fail_highs += other.first_move_fail_highs;
+        self.qnodes += other.qnodes;
+    }
+}
+
+fn bound_index(bound: &Bound) -> usize {
+    match bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    }
+}
+
+fn ratio(count: u64, total: u64) -> String {
+    format!("{:.1}% ({count}/{total})", count as f64 * 100.0 / total.max(1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_tt_cutoffs_by_bound() {
+        let mut stats = SearchStats::default();
+
+        stats.tt_cutoff(&Bound::Lower);
+        stats.tt_cutoff(&Bound::Lower);
+        stats.tt_cutoff(&Bound::Upper);
+
+        assert_eq!(stats.tt_cutoffs, [0, 2, 1]);
+    }
+
+    #[test]
+    fn count_first_move_fail_highs() {
+        let mut stats = SearchStats::default();
+
+        stats.fail_high(1);
+        stats.fail_high(3);
+
+        assert_eq!(stats.fail_highs, 2);
+        assert_eq!(stats.first_move_fail_highs, 1);
+    }
+
+    #[test]
+    fn combine_stats_from_several_searches() {
+        let mut stats = SearchStats {
+            qnodes: 10,
+            tt_cutoffs: [1, 2, 3],
+            ..Default::default()
+        };
+
+        stats += SearchStats {
+            qnodes: 5,
+            tt_cutoffs: [1, 0, 0],
+            ..Default::default()
+        };
+
+        assert_eq!(stats.qnodes, 15);
+        assert_eq!(stats.tt_cutoffs, [2, 2, 3]);
+    }
+
+    #[test]
+    fn summarise_ratios_as_percentages() {
+        let stats = SearchStats {
+            qnodes: 25,
+            ..Default::default()
+        };
+
+        assert!(
+            stats
+                .summary(100)
+                .contains(&"quiescence nodes 25.0% (25/100)".to_string())
+        );
+    }
+}
@@ -4,6 +4,7 @@ use crate::eval::EVAL_MIN;
 use crate::movegen::Move;
 use std::cell::Cell;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 const STOPPER_NODES_MASK: u128 = 255;
 const SOFT_STOP_MIN_DEPTH: u8 = 5;
@@ -11,12 +12,15 @@ const EVAL_MULTIPLIER_MIN: f32 = 0.5;
 const EVAL_MULTIPLIER_MAX: f32 = 1.5;
 const EVAL_MULTIPLIER_SCALE: f32 = 200.0;
 const BEST_MOVE_MULTIPLIERS: [f32; 5] = [2.0, 1.2, 0.9, 0.8, 0.75];
+const NODE_FRACTION_BASE: f32 = 1.5;
+const NODE_FRACTION_SCALE: f32 = 1.35;
 
 pub struct Stopper<'a> {
     pub depth: Option<u8>,
     time: Option<TimeLimit>,
     eval: Option<i32>,
     nodes: Option<u128>,
+    nps: Option<u128>,
     signal: Option<&'a AtomicBool>,
     stability: Cell<SearchStability>,
 }
@@ -28,6 +32,7 @@ impl<'a> Stopper<'a> {
             time: None,
             eval: None,
             nodes: None,
+            nps: None,
             signal: None,
             stability: Cell::new(SearchStability::default()),
         }
@@ -49,6 +54,13 @@ impl<'a> Stopper<'a> {
         self.nodes = nodes;
     }
 
+    // Measure time by the nodes searched at a fixed rate rather than by the
+    // clock, so that a search with a time limit stops at the same point on
+    // every run.
+    pub fn at_nps(&mut self, nps: Option<u128>) {
+        self.nps = nps;
+    }
+
     pub fn at_signal(&mut self, signal: &'a AtomicBool) {
         self.signal = Some(signal);
     }
@@ -64,8 +76,10 @@ impl<'a> Stopper<'a> {
             return true;
         }
 
+        // Always complete the first iteration so that there's a move to play.
         if let Some(time) = &self.time
-            && report.elapsed() > time.hard()
+            && report.depth > 0
+            && self.elapsed(report) > time.hard()
         {
             return true;
         }
@@ -107,8 +121,13 @@ impl<'a> Stopper<'a> {
             let eval_multiplier = (1.0 + (stability.best_eval - eval) as f32 / EVAL_MULTIPLIER_SCALE)
                 .clamp(EVAL_MULTIPLIER_MIN, EVAL_MULTIPLIER_MAX);
 
-            let adjusted = soft.mul_f32(best_move_multiplier * eval_multiplier);
-            report.elapsed() > adjusted.min(*hard)
+            // Spend less time when most of the search effort has gone into the
+            // best move, since it's unlikely to be overturned by another move.
+            let node_fraction = report.best_move_nodes as f32 / report.nodes.max(1) as f32;
+            let node_multiplier = (NODE_FRACTION_BASE - node_fraction) * NODE_FRACTION_SCALE;
+
+            let adjusted = soft.mul_f32(best_move_multiplier * eval_multiplier * node_multiplier);
+            self.elapsed(report) > adjusted.min(*hard)
         };
 
         stability.last_best_move = best_move;
@@ -117,6 +136,13 @@ impl<'a> Stopper<'a> {
 
         has_elapsed
     }
+
+    fn elapsed(&self, report: &Report) -> Duration {
+        match self.nps {
+            Some(nps) => Duration::from_micros((report.nodes * 1_000_000 / nps) as u64),
+            None => report.elapsed(),
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -135,3 +161,24 @@ impl Default for SearchStability {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_time_in_nodes_at_a_fixed_nps() {
+        let mut stopper = Stopper::new();
+        stopper.at_time(Some(TimeLimit::fixed(Duration::from_millis(100), Duration::ZERO)));
+        stopper.at_nps(Some(10_000));
+
+        let mut report = Report::new();
+        report.depth = 1;
+
+        report.nodes = 512;
+        assert!(!stopper.should_stop(&report));
+
+        report.nodes = 1280;
+        assert!(stopper.should_stop(&report));
+    }
+}
@@ -1,32 +1,60 @@
+use crate::eval::phase::MAX_PHASE;
 use std::time::Duration;
 
+const MOVES_LEFT_MIN: u32 = 20;
+const MOVES_LEFT_MAX: u32 = 40;
+
 pub enum TimeLimit {
     Dynamic { soft: Duration, hard: Duration },
     Fixed(Duration),
 }
 
 impl TimeLimit {
-    pub fn fixed(duration: Duration) -> Self {
-        Self::Fixed(duration)
+    pub fn fixed(duration: Duration, move_overhead: Duration) -> Self {
+        Self::Fixed(duration.saturating_sub(move_overhead))
     }
 
     //
-    // Calculate time budget based on time left and increment per move.
+    // Calculate time budget based on time left, increment, moves to go and game phase.
     //
+    //   - Deduct the move overhead from time left to allow for communication lag.
+    //   - Estimate moves left as moves to go (capped at 40), or for sudden death,
+    //     interpolate from 40 in the opening down to 20 in the endgame by phase.
     //   - Compute a "reserve" (minimum time to always keep) as max(time left / 20, 50ms).
-    //   - Subtract reserve from time left to get max time that can be used for this move.
-    //   - Soft limit: min(time left / 30 + increment * 3/4, max time).
+    //   - Max time is time left minus reserve. With moves to go, the time must also
+    //     last until the next time control, so limit it to 2/(moves left + 1) of time left.
+    //   - Soft limit: min(time left / moves left + increment * 3/4, max time).
     //   - Hard limit: min(soft * 3, max time).
     //
-    pub fn dynamic(time_left: Duration, time_inc: Option<Duration>) -> Self {
+    pub fn dynamic(
+        time_left: Duration,
+        time_inc: Option<Duration>,
+        moves_to_go: Option<u8>,
+        move_overhead: Duration,
+        phase: i32,
+    ) -> Self {
+        let time_left = time_left.saturating_sub(move_overhead);
+
         if time_left.as_millis() == 0 {
             return Self::Fixed(time_left);
         }
 
+        let moves_left = match moves_to_go {
+            Some(moves) => (moves as u32).clamp(1, MOVES_LEFT_MAX),
+            None => {
+                let phase = phase.clamp(0, MAX_PHASE) as u32;
+                MOVES_LEFT_MIN + (MOVES_LEFT_MAX - MOVES_LEFT_MIN) * phase / MAX_PHASE as u32
+            }
+        };
+
         let reserve = (time_left / 20).max(Duration::from_millis(50));
-        let max_time = time_left.saturating_sub(reserve);
+        let mut max_time = time_left.saturating_sub(reserve);
+
+        if moves_to_go.is_some() {
+            max_time = max_time.min(time_left * 2 / (moves_left + 1));
+        }
 
-        let soft = (time_left / 30 + time_inc.unwrap_or_default() * 3 / 4).min(max_time);
+        let soft = (time_left / moves_left + time_inc.unwrap_or_default() * 3 / 4).min(max_time);
         let hard = (soft * 3).min(max_time);
 
         Self::Dynamic { soft, hard }
@@ -39,3 +67,81 @@ impl TimeLimit {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_OVERHEAD: Duration = Duration::ZERO;
+
+    #[test]
+    fn deduct_move_overhead_from_fixed_time() {
+        let time = TimeLimit::fixed(ms(1000), ms(30));
+
+        assert_eq!(time.hard(), ms(970));
+    }
+
+    #[test]
+    fn spend_more_time_per_move_in_the_endgame() {
+        let (opening_soft, _) = dynamic(ms(60_000), None, None, NO_OVERHEAD, MAX_PHASE);
+        let (endgame_soft, _) = dynamic(ms(60_000), None, None, NO_OVERHEAD, 0);
+
+        assert_eq!(opening_soft, ms(1500));
+        assert_eq!(endgame_soft, ms(3000));
+    }
+
+    #[test]
+    fn spend_more_time_per_move_with_an_increment() {
+        let (soft, hard) = dynamic(ms(60_000), Some(ms(1000)), None, NO_OVERHEAD, MAX_PHASE);
+
+        assert_eq!(soft, ms(2250));
+        assert_eq!(hard, ms(6750));
+    }
+
+    #[test]
+    fn divide_time_by_moves_to_go() {
+        let (soft, hard) = dynamic(ms(60_000), None, Some(4), NO_OVERHEAD, MAX_PHASE);
+
+        assert_eq!(soft, ms(15_000));
+        assert_eq!(hard, ms(24_000));
+    }
+
+    #[test]
+    fn keep_a_reserve_on_the_last_move_before_the_time_control() {
+        let (soft, hard) = dynamic(ms(10_000), None, Some(1), NO_OVERHEAD, MAX_PHASE);
+
+        assert_eq!(soft, ms(9500));
+        assert_eq!(hard, ms(9500));
+    }
+
+    #[test]
+    fn deduct_move_overhead_from_time_left() {
+        let (soft, _) = dynamic(ms(60_100), None, Some(10), ms(100), MAX_PHASE);
+
+        assert_eq!(soft, ms(6000));
+    }
+
+    #[test]
+    fn use_no_time_when_overhead_exceeds_time_left() {
+        let time = TimeLimit::dynamic(ms(50), None, None, ms(100), MAX_PHASE);
+
+        assert!(matches!(time, TimeLimit::Fixed(duration) if duration.is_zero()));
+    }
+
+    fn dynamic(
+        time_left: Duration,
+        time_inc: Option<Duration>,
+        moves_to_go: Option<u8>,
+        move_overhead: Duration,
+        phase: i32,
+    ) -> (Duration, Duration) {
+        match TimeLimit::dynamic(time_left, time_inc, moves_to_go, move_overhead, phase) {
+            TimeLimit::Dynamic { soft, hard } => (soft, hard),
+            TimeLimit::Fixed(_) => panic!("expected a dynamic time limit"),
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+}
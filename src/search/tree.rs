@@ -0,0 +1,420 @@
+use super::tt::Bound;
+use crate::movegen::Move;
+use crate::uci::r#move::UciMove;
+use std::io::{self, Write};
+
+// Stop recording beyond this many nodes since deeper searches visit millions
+// of them, which would make for a file too large to be of any use.
+pub const MAX_NODES: usize = 1_000_000;
+
+// Limits on which parts of the tree to record.
+#[derive(Clone, Default)]
+pub struct TreeFilter {
+    pub max_ply: Option<u16>,
+    pub root_move: Option<UciMove>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeMove {
+    Root,
+    Null,
+    Move(UciMove),
+}
+
+// Why a node returned before searching all of its moves, or for pruned moves,
+// why they weren't searched at all.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cutoff {
+    Stopped,
+    Draw,
+    MateDistance,
+    Quiescence,
+    Tt,
+    Rfp,
+    NullMove,
+    Lmp,
+    Futility,
+    Beta,
+}
+
+const CUTOFF_NAMES: [(Cutoff, &str); 10] = [
+    (Cutoff::Stopped, "stopped"),
+    (Cutoff::Draw, "draw"),
+    (Cutoff::MateDistance, "mate-distance"),
+    (Cutoff::Quiescence, "quiescence"),
+    (Cutoff::Tt, "tt"),
+    (Cutoff::Rfp, "rfp"),
+    (Cutoff::NullMove, "null-move"),
+    (Cutoff::Lmp, "lmp"),
+    (Cutoff::Futility, "futility"),
+    (Cutoff::Beta, "beta"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeNode {
+    pub parent: Option<usize>,
+    pub ply: u16,
+    pub mv: NodeMove,
+    pub depth: u8,
+    pub alpha: i32,
+    pub beta: i32,
+    // Pruned moves are recorded without ever being searched, so have no eval.
+    pub eval: Option<i32>,
+    pub bound: Option<Bound>,
+    pub cutoff: Option<Cutoff>,
+    pub reduction: u8,
+}
+
+// Records the nodes visited by a search in the order they're entered, so that
+// each node comes after its parent and the children of a node are in the
+// order they were searched. Every root search, including each iteration and
+// aspiration re-search, starts a new tree.
+pub struct SearchTree {
+    pub nodes: Vec<TreeNode>,
+    pub is_truncated: bool,
+    filter: TreeFilter,
+    // The recorded node at each ply of the current line, or none where it was
+    // filtered out, in which case nothing below it is recorded either.
+    stack: Vec<Option<usize>>,
+    next_move: NodeMove,
+    next_reduction: u8,
+}
+
+impl SearchTree {
+    pub fn new(filter: TreeFilter) -> Self {
+        Self {
+            nodes: vec![],
+            is_truncated: false,
+            filter,
+            stack: vec![],
+            next_move: NodeMove::Root,
+            next_reduction: 0,
+        }
+    }
+
+    // Called before searching a child node, since the child doesn't know
+    // which move led to it or how much it was reduced by.
+    pub fn next(&mut self, mv: Option<Move>, reduction: u8) {
+        self.next_move = match mv {
+            Some(mv) => NodeMove::Move(mv.into()),
+            None => NodeMove::Null,
+        };
+        self.next_reduction = reduction;
+    }
+
+    pub fn enter(&mut self, ply: u16, depth: u8, alpha: i32, beta: i32) {
+        let parent = self.stack.last().copied().flatten();
+        let mv = std::mem::replace(&mut self.next_move, NodeMove::Root);
+        let reduction = std::mem::take(&mut self.next_reduction);
+
+        let index = self.should_record(ply, parent, mv).then(|| {
+            self.nodes.push(TreeNode {
+                parent,
+                ply,
+                mv,
+                depth,
+                alpha,
+                beta,
+                eval: None,
+                bound: None,
+                cutoff: None,
+                reduction,
+            });
+            self.nodes.len() - 1
+        });
+
+        self.stack.push(index);
+    }
+
+    pub fn exit(&mut self, eval: i32) {
+        if let Some(Some(index)) = self.stack.pop() {
+            let node = &mut self.nodes[index];
+            node.eval = Some(eval);
+            node.bound = Some(if eval <= node.alpha {
+                Bound::Upper
+            } else if eval >= node.beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            });
+        }
+    }
+
+    pub fn cutoff(&mut self, cutoff: Cutoff) {
+        if let Some(&Some(index)) = self.stack.last() {
+            self.nodes[index].cutoff = Some(cutoff);
+        }
+    }
+
+    // Record a move that was pruned as a leaf, since it's never searched.
+    pub fn prune(&mut self, mv: Move, depth: u8, alpha: i32, beta: i32, cutoff: Cutoff) {
+        let ply = self.stack.len() as u16;
+        let parent = self.stack.last().copied().flatten();
+        let mv = NodeMove::Move(mv.into());
+
+        if self.should_record(ply, parent, mv) {
+            self.nodes.push(TreeNode {
+                parent,
+                ply,
+                mv,
+                depth,
+                alpha,
+                beta,
+                eval: None,
+                bound: None,
+                cutoff: Some(cutoff),
+                reduction: 0,
+            });
+        }
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "# id parent ply move depth alpha beta eval bound cutoff reduction")?;
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            writeln!(
+                out,
+                "{id} {} {} {} {} {} {} {} {} {} {}",
+                optional(node.parent),
+                node.ply,
+                move_name(node.mv),
+                node.depth,
+                node.alpha,
+                node.beta,
+                optional(node.eval),
+                optional(node.bound.as_ref().map(bound_name)),
+                optional(node.cutoff.map(cutoff_name)),
+                node.reduction,
+            )?;
+        }
+
+        out.flush()
+    }
+
+    fn should_record(&mut self, ply: u16, parent: Option<usize>, mv: NodeMove) -> bool {
+        if ply > 0 && parent.is_none() {
+            return false;
+        }
+
+        if self.filter.max_ply.is_some_and(|max_ply| ply > max_ply) {
+            return false;
+        }
+
+        if ply == 1
+            && let Some(root_move) = self.filter.root_move
+            && mv != NodeMove::Move(root_move)
+        {
+            return false;
+        }
+
+        if self.nodes.len() >= MAX_NODES {
+            self.is_truncated = true;
+            return false;
+        }
+
+        true
+    }
+}
+
+// Read back the nodes of a tree written by `SearchTree::write()`.
+pub fn parse_nodes(input: &str) -> Result<Vec<TreeNode>, String> {
+    let mut nodes = vec![];
+
+    for line in input.lines().filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let fields: Vec<_> = line.split_whitespace().collect();
+
+        let [id, parent, ply, mv, depth, alpha, beta, eval, bound, cutoff, reduction] = fields[..] else {
+            return Err(format!("invalid tree node '{line}'"));
+        };
+
+        if id.parse() != Ok(nodes.len()) {
+            return Err(format!("unexpected tree node id '{id}'"));
+        }
+
+        let parent = parse_optional(parent, parse_number)?;
+
+        if parent.is_some_and(|parent| parent >= nodes.len()) {
+            return Err(format!("unknown parent for tree node '{id}'"));
+        }
+
+        nodes.push(TreeNode {
+            parent,
+            ply: parse_number(ply)?,
+            mv: parse_move(mv)?,
+            depth: parse_number(depth)?,
+            alpha: parse_number(alpha)?,
+            beta: parse_number(beta)?,
+            eval: parse_optional(eval, parse_number)?,
+            bound: parse_optional(bound, parse_bound)?,
+            cutoff: parse_optional(cutoff, parse_cutoff)?,
+            reduction: parse_number(reduction)?,
+        });
+    }
+
+    Ok(nodes)
+}
+
+pub fn move_name(mv: NodeMove) -> String {
+    match mv {
+        NodeMove::Root => "root".to_string(),
+        NodeMove::Null => "null".to_string(),
+        NodeMove::Move(mv) => mv.to_string(),
+    }
+}
+
+pub fn bound_name(bound: &Bound) -> &'static str {
+    match bound {
+        Bound::Exact => "exact",
+        Bound::Lower => "lower",
+        Bound::Upper => "upper",
+    }
+}
+
+pub fn cutoff_name(cutoff: Cutoff) -> &'static str {
+    CUTOFF_NAMES.iter().find(|(c, _)| *c == cutoff).unwrap().1
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
+
+fn parse_optional<T>(field: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, String> {
+    if field == "-" { Ok(None) } else { parse(field).map(Some) }
+}
+
+fn parse_number<T: std::str::FromStr>(field: &str) -> Result<T, String> {
+    field
+        .parse()
+        .map_err(|_| format!("invalid number '{field}' in tree node"))
+}
+
+fn parse_move(field: &str) -> Result<NodeMove, String> {
+    match field {
+        "root" => Ok(NodeMove::Root),
+        "null" => Ok(NodeMove::Null),
+        _ => Ok(NodeMove::Move(field.parse()?)),
+    }
+}
+
+fn parse_bound(field: &str) -> Result<Bound, String> {
+    match field {
+        "exact" => Ok(Bound::Exact),
+        "lower" => Ok(Bound::Lower),
+        "upper" => Ok(Bound::Upper),
+        _ => Err(format!("invalid bound '{field}' in tree node")),
+    }
+}
+
+fn parse_cutoff(field: &str) -> Result<Cutoff, String> {
+    match CUTOFF_NAMES.iter().find(|(_, name)| *name == field) {
+        Some((cutoff, _)) => Ok(*cutoff),
+        None => Err(format!("invalid cutoff '{field}' in tree node")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::Colour;
+    use crate::piece::Piece;
+    use crate::square::Square;
+    use crate::testing::*;
+
+    #[test]
+    fn record_nodes_with_their_parents() {
+        let mut tree = SearchTree::new(TreeFilter::default());
+        let e4 = make_move(Piece::WP, Square::E2, Square::E4, None);
+
+        tree.enter(0, 2, -100, 100);
+        tree.next(Some(e4), 1);
+        tree.enter(1, 0, -100, 100);
+        tree.exit(-20);
+        tree.cutoff(Cutoff::Beta);
+        tree.exit(100);
+
+        assert_eq!(tree.nodes.len(), 2);
+        assert_eq!(tree.nodes[0].cutoff, Some(Cutoff::Beta));
+        assert_eq!(tree.nodes[0].bound, Some(Bound::Lower));
+        assert_eq!(tree.nodes[1].parent, Some(0));
+        assert_eq!(tree.nodes[1].mv, NodeMove::Move(e4.into()));
+        assert_eq!(tree.nodes[1].reduction, 1);
+        assert_eq!(tree.nodes[1].bound, Some(Bound::Exact));
+    }
+
+    #[test]
+    fn only_record_nodes_up_to_the_maximum_ply() {
+        let mut tree = SearchTree::new(TreeFilter {
+            max_ply: Some(1),
+            root_move: None,
+        });
+
+        tree.enter(0, 3, -100, 100);
+        tree.next(None, 2);
+        tree.enter(1, 2, -100, 100);
+        tree.next(None, 2);
+        tree.enter(2, 1, -100, 100);
+        tree.exit(0);
+        tree.exit(0);
+        tree.exit(0);
+
+        assert_eq!(tree.nodes.len(), 2);
+    }
+
+    #[test]
+    fn only_record_the_root_move_being_filtered_for() {
+        let e4 = make_move(Piece::WP, Square::E2, Square::E4, None);
+        let d4 = make_move(Piece::WP, Square::D2, Square::D4, None);
+        let mut tree = SearchTree::new(TreeFilter {
+            max_ply: None,
+            root_move: Some(d4.into()),
+        });
+
+        tree.enter(0, 2, -100, 100);
+        for mv in [e4, d4] {
+            tree.next(Some(mv), 0);
+            tree.enter(1, 1, -100, 100);
+            tree.prune(
+                make_move(Piece::BP, Square::E7, Square::E5, None),
+                0,
+                -100,
+                100,
+                Cutoff::Lmp,
+            );
+            tree.exit(0);
+        }
+        tree.exit(0);
+
+        assert_eq!(tree.nodes.len(), 3);
+        assert_eq!(tree.nodes[1].mv, NodeMove::Move(d4.into()));
+        assert_eq!(tree.nodes[2].parent, Some(1));
+        assert_eq!(tree.nodes[2].eval, None);
+    }
+
+    #[test]
+    fn read_back_a_written_tree() {
+        let mut tree = SearchTree::new(TreeFilter::default());
+
+        tree.enter(0, 3, -100, 100);
+        tree.next(None, 2);
+        tree.enter(1, 0, -100, -99);
+        tree.cutoff(Cutoff::Rfp);
+        tree.exit(-99);
+        tree.next(
+            Some(make_promotion_move(Colour::White, Square::B7, Square::B8, Piece::WQ)),
+            0,
+        );
+        tree.enter(1, 2, -100, 100);
+        tree.exit(-50);
+        tree.exit(50);
+
+        let mut out = vec![];
+        tree.write(&mut out).unwrap();
+
+        assert_eq!(parse_nodes(&String::from_utf8(out).unwrap()), Ok(tree.nodes));
+    }
+
+    #[test]
+    fn reject_nodes_with_unknown_parents() {
+        assert!(parse_nodes("0 1 0 root 3 -100 100 0 exact - 0").is_err());
+    }
+}
@@ -21,7 +21,7 @@ pub struct Entry {
     age: u8,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Bound {
     Exact,
     Lower,
@@ -114,7 +114,7 @@ impl TranspositionTable {
 // offset by the current ply to reflect distance to mate from the root. Non-mate
 // evals are returned unchanged.
 #[inline]
-pub fn eval_in(eval: i32, ply: u8) -> i32 {
+pub fn eval_in(eval: i32, ply: u16) -> i32 {
     if eval >= EVAL_MATE_THRESHOLD {
         eval + ply as i32
     } else if eval <= -EVAL_MATE_THRESHOLD {
@@ -128,7 +128,7 @@ pub fn eval_in(eval: i32, ply: u8) -> i32 {
 // removing the ply offset from mate evals to recover the correct eval at the
 // current node. Non-mate evals are returned unchanged.
 #[inline]
-pub fn eval_out(eval: i32, ply: u8) -> i32 {
+pub fn eval_out(eval: i32, ply: u16) -> i32 {
     if eval >= EVAL_MATE_THRESHOLD {
         eval - ply as i32
     } else if eval <= -EVAL_MATE_THRESHOLD {
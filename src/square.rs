@@ -67,7 +67,7 @@ impl Square {
         self.0
     }
 
-    pub fn u64(&self) -> u64 {
+    pub const fn u64(&self) -> u64 {
         1 << self.0
     }
 
@@ -87,6 +87,11 @@ impl Square {
         self.rank().abs_diff(other.rank())
     }
 
+    // The number of king moves it takes to get from one square to the other.
+    pub fn distance(&self, other: Square) -> u8 {
+        self.file_diff(other).max(self.rank_diff(other))
+    }
+
     pub fn advance(&self, colour: Colour) -> Self {
         match colour {
             Colour::White => Self(self.0 + 8),
@@ -127,14 +132,9 @@ impl std::str::FromStr for Square {
     type Err = String;
 
     fn from_str(square: &str) -> Result<Self, Self::Err> {
-        if square.len() != 2 {
+        let &[file, rank] = square.as_bytes() else {
             return Err(format!("invalid square '{square}'"));
-        }
-
-        let chars: Vec<_> = square.chars().collect();
-
-        let file = chars[0] as u8;
-        let rank = chars[1] as u8;
+        };
 
         if !(b'a'..=b'h').contains(&file) || !(b'1'..=b'8').contains(&rank) {
             return Err(format!("invalid square '{square}'"));
@@ -189,11 +189,19 @@ mod tests {
 
     #[test]
     fn it_cannot_be_created_from_invalid_algebraic_notation() {
-        for str in ["", "a", "a1b", "a9", "i1"] {
+        for str in ["", "a", "a1b", "a9", "i1", "é"] {
             assert!(str.parse::<Square>().is_err());
         }
     }
 
+    #[test]
+    fn distance_between_squares() {
+        assert_eq!(Square::A1.distance(Square::A1), 0);
+        assert_eq!(Square::A1.distance(Square::H8), 7);
+        assert_eq!(Square::E1.distance(Square::G8), 7);
+        assert_eq!(Square::D1.distance(Square::B2), 2);
+    }
+
     #[test]
     fn advance_a_square_given_a_colour() {
         assert_eq!(Square::E5, Square::E4.advance(Colour::White));
@@ -1,4 +1,5 @@
 use crate::colour::Colour;
+use crate::eval::terms::{Attacks, EvalTerm, Term};
 use crate::movegen::Move;
 use crate::piece::Piece;
 use crate::position::Position;
@@ -32,6 +33,10 @@ pub fn make_promotion_move(colour: Colour, from: Square, to: Square, piece: Piec
     }
 }
 
+pub fn eval_term(term: Term, colour: Colour, pos: &Position) -> EvalTerm {
+    term(colour, pos, &Attacks::new(&pos.board))
+}
+
 // Define the remaining squares for testing purposes
 impl Square {
     pub const A2: Self = Self::from_index(8);
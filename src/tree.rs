@@ -0,0 +1,114 @@
+use crate::search::tree::{self, TreeNode};
+use std::fs;
+
+pub fn run(cli_args: &[String]) {
+    let mut path = None;
+    let mut format = "text".to_string();
+    let mut iter = cli_args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => format = iter.next().unwrap().to_lowercase(),
+            _ => path = Some(arg),
+        }
+    }
+
+    let Some(path) = path else {
+        println!("usage: tree <file> [--format text|dot]");
+        return;
+    };
+
+    let nodes = match fs::read_to_string(path).map_err(|err| err.to_string()) {
+        Ok(input) => tree::parse_nodes(&input),
+        Err(err) => Err(err),
+    };
+
+    match (nodes, format.as_str()) {
+        (Ok(nodes), "text") => print!("{}", render_text(&nodes)),
+        (Ok(nodes), "dot") => print!("{}", render_dot(&nodes)),
+        (Ok(_), _) => println!("unknown format '{format}'"),
+        (Err(err), _) => println!("could not read search tree: {err}"),
+    }
+}
+
+// Nodes are recorded parent first with children in search order, so indenting
+// each node by its ply is enough to show the tree.
+fn render_text(nodes: &[TreeNode]) -> String {
+    nodes
+        .iter()
+        .map(|node| format!("{}{}\n", "  ".repeat(node.ply as usize), describe(node).join(" ")))
+        .collect()
+}
+
+fn render_dot(nodes: &[TreeNode]) -> String {
+    let mut dot = "digraph tree {\n    node [shape=box, fontname=monospace];\n".to_string();
+
+    for (id, node) in nodes.iter().enumerate() {
+        // Pruned moves were never searched.
+        let style = if node.eval.is_none() { ", style=dashed" } else { "" };
+        dot += &format!("    n{id} [label=\"{}\"{style}];\n", describe(node).join("\\n"));
+
+        if let Some(parent) = node.parent {
+            dot += &format!("    n{parent} -> n{id};\n");
+        }
+    }
+
+    dot + "}\n"
+}
+
+fn describe(node: &TreeNode) -> Vec<String> {
+    let mut parts = vec![
+        tree::move_name(node.mv),
+        format!("depth {}", node.depth),
+        format!("window [{}, {}]", node.alpha, node.beta),
+    ];
+
+    if let (Some(eval), Some(bound)) = (node.eval, &node.bound) {
+        parts.push(format!("eval {eval} {}", tree::bound_name(bound)));
+    }
+
+    if node.reduction > 0 {
+        parts.push(format!("reduction {}", node.reduction));
+    }
+
+    if let Some(cutoff) = node.cutoff {
+        parts.push(format!("cutoff {}", tree::cutoff_name(cutoff)));
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TREE: &str = "\
+# id parent ply move depth alpha beta eval bound cutoff reduction
+0 - 0 root 2 -100 100 30 exact - 0
+1 0 1 e2e4 1 -100 100 -30 exact - 0
+2 0 1 a2a3 0 -100 100 - - futility 0
+";
+
+    #[test]
+    fn render_the_tree_as_indented_text() {
+        let nodes = tree::parse_nodes(TREE).unwrap();
+
+        assert_eq!(
+            render_text(&nodes),
+            "root depth 2 window [-100, 100] eval 30 exact\n  \
+             e2e4 depth 1 window [-100, 100] eval -30 exact\n  \
+             a2a3 depth 0 window [-100, 100] cutoff futility\n"
+        );
+    }
+
+    #[test]
+    fn render_the_tree_as_a_graph() {
+        let nodes = tree::parse_nodes(TREE).unwrap();
+        let dot = render_dot(&nodes);
+
+        assert!(dot.starts_with("digraph tree {"));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n0 -> n2;"));
+        assert!(dot.contains("n2 [label=\"a2a3\\ndepth 0\\nwindow [-100, 100]\\ncutoff futility\", style=dashed];"));
+    }
+}
@@ -1,24 +1,34 @@
+use crate::eval::{material::MaterialEval, trace::trace};
 use crate::info;
-use crate::movegen::{Move, perft};
-use crate::piece::Piece;
+use crate::movegen::{Move, generate_legal_moves, perft};
 use crate::position::Position;
+use crate::rng::XorShift64;
 use crate::search::{
-    search,
+    mate,
+    params::SearchParams,
+    pns::{self, SolutionNode},
+    search, search_with_evaluator,
+    skill::{self, Skill},
     stopper::Stopper,
-    tt::{self, TranspositionTable},
+    tree::TreeFilter,
+    tt::TranspositionTable,
 };
-use crate::uci::{r#move::UciMove, reporter::UciReporter};
-use std::time::Instant;
+use crate::uci::{
+    command::PerftParams,
+    r#move::UciMove,
+    options::{self, OPTIONS, Options},
+    reporter::UciReporter,
+};
+use std::fs;
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 pub fn init() {
     println!("id name {}", info::name());
     println!("id author {}", info::author());
-    println!(
-        "option name Hash type spin default {} min {} max {}",
-        tt::DEFAULT_SIZE_MB,
-        tt::MIN_SIZE_MB,
-        tt::MAX_SIZE_MB
-    );
+    for option in OPTIONS {
+        println!("{option}");
+    }
     println!("uciok");
 }
 
@@ -39,9 +49,35 @@ pub fn print_fen(pos: &Position) {
     println!("{}", pos.to_fen());
 }
 
-pub fn perft(depth: u8, pos: &Position) {
+pub fn eval(pos: &Position) {
+    println!("{}", trace(pos));
+}
+
+pub fn perft(params: PerftParams, pos: &Position) {
     let started_at = Instant::now();
-    let nodes = perft::divide(&mut pos.clone(), depth);
+
+    let nodes = if params.stats {
+        let stats = perft::perft_stats(&mut pos.clone(), params.depth);
+
+        println!("\ncaptures: {}", stats.captures);
+        println!("en passants: {}", stats.en_passants);
+        println!("castles: {}", stats.castles);
+        println!("promotions: {}", stats.promotions);
+        println!("checks: {}", stats.checks);
+        println!("checkmates: {}", stats.checkmates);
+
+        stats.nodes
+    } else {
+        let threads = params.threads.unwrap_or_else(available_threads);
+        let counts = perft::divide(pos, params.depth, threads, params.hash_mb);
+
+        for (mv, count) in &counts {
+            println!("{}: {count}", UciMove::from(*mv));
+        }
+
+        total_nodes(params.depth, &counts)
+    };
+
     let elapsed = started_at.elapsed();
     let ms = elapsed.as_millis();
     let nps = nodes * 1000 / ms.max(1);
@@ -51,59 +87,259 @@ pub fn perft(depth: u8, pos: &Position) {
     println!("nps: {nps}\n");
 }
 
-pub fn do_move(mv: UciMove, pos: &mut Position) {
-    let piece = pos.board.piece_at(mv.from).unwrap();
-    let is_en_passant = piece.is_pawn() && pos.en_passant_square == Some(mv.to);
+// Check perft counts against a suite of positions. Positions which can't be
+// loaded, such as Chess960 ones, are skipped rather than failed.
+pub fn perft_suite(path: &str, max_depth: Option<u8>) {
+    let suite = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|input| perft::parse_suite(&input));
 
-    let captured_piece = if is_en_passant {
-        Some(Piece::pawn(pos.colour_to_move.flip()))
-    } else {
-        pos.board.piece_at(mv.to)
+    let suite = match suite {
+        Ok(suite) => suite,
+        Err(err) => {
+            println!("info string error: {err}");
+            return;
+        }
     };
 
-    pos.do_move(&Move {
-        piece,
-        from: mv.from,
-        to: mv.to,
-        captured_piece,
-        promotion_piece: mv.promotion_piece,
-        is_en_passant,
-    });
+    let started_at = Instant::now();
+    let threads = available_threads();
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+
+    for (index, entry) in suite.iter().enumerate() {
+        let number = format!("{}/{}", index + 1, suite.len());
+
+        let pos = match entry.fen.parse::<Position>() {
+            Ok(pos) => pos.validate().map(|_| pos),
+            Err(err) => Err(err),
+        };
+
+        let pos = match pos {
+            Ok(pos) => pos,
+            Err(err) => {
+                println!("{number} skipped {}: {err}", entry.fen);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let counts: Vec<_> = entry
+            .counts
+            .iter()
+            .filter(|(depth, _)| max_depth.is_none_or(|max_depth| *depth <= max_depth))
+            .collect();
+
+        // Don't let a low max depth pass positions without checking them.
+        if counts.is_empty() {
+            println!("{number} skipped {}: no counts within the max depth", entry.fen);
+            skipped += 1;
+            continue;
+        }
+
+        let failures: Vec<_> = counts
+            .iter()
+            .filter_map(|&&(depth, expected)| {
+                let counts = perft::divide(&pos, depth, threads, perft::DEFAULT_HASH_MB);
+                let nodes = total_nodes(depth, &counts);
+
+                (nodes != expected).then(|| format!("D{depth} expected {expected} got {nodes}"))
+            })
+            .collect();
+
+        if failures.is_empty() {
+            let depths: Vec<_> = counts.iter().map(|(depth, _)| format!("D{depth}")).collect();
+            println!("{number} ok {}: {}", entry.fen, depths.join(" "));
+            passed += 1;
+        } else {
+            println!("{number} failed {}: {}", entry.fen, failures.join(", "));
+            failed += 1;
+        }
+    }
+
+    println!("\npassed: {passed}");
+    println!("failed: {failed}");
+    println!("skipped: {skipped}");
+    println!("time: {} ms\n", started_at.elapsed().as_millis());
 }
 
-pub fn position(fen: String, moves: Vec<UciMove>, pos: &mut Position) {
-    *pos = fen.parse().unwrap();
+// There are no root moves to divide at depth 0, where the root itself is the
+// only node.
+fn total_nodes(depth: u8, counts: &[(Move, u128)]) -> u128 {
+    if depth == 0 { 1 } else { counts.iter().map(|(_, count)| count).sum() }
+}
+
+fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+pub fn solve(max_nodes: usize, pos: &Position) {
+    let started_at = Instant::now();
+    let solution = pns::solve(&mut pos.clone(), max_nodes);
+    let ms = started_at.elapsed().as_millis();
+
+    println!("\nresult: {:?}", solution.outcome);
+    println!("nodes: {}", solution.nodes);
+    println!("time: {ms} ms\n");
+
+    print_solution_tree(&solution.tree, 0);
+}
+
+fn print_solution_tree(tree: &[SolutionNode], depth: usize) {
+    for node in tree {
+        println!("{}{}", "  ".repeat(depth), UciMove::from(node.mv));
+        print_solution_tree(&node.children, depth + 1);
+    }
+}
+
+pub fn do_move(mv: UciMove, pos: &mut Position) -> Result<(), String> {
+    let legal_move = generate_legal_moves(pos)
+        .into_iter()
+        .find(|legal_move| UciMove::from(*legal_move) == mv);
+
+    let Some(legal_move) = legal_move else {
+        return Err(format!("illegal move '{mv}'"));
+    };
+
+    pos.do_move(&legal_move);
+
+    Ok(())
+}
+
+// Build the new position separately so that the current one is left unchanged
+// if the FEN is invalid or any of the moves are illegal.
+pub fn position(fen: String, moves: Vec<UciMove>, pos: &mut Position) -> Result<(), String> {
+    let mut new_pos: Position = fen.parse()?;
+    new_pos.validate()?;
 
     for mv in moves {
-        do_move(mv, pos);
+        do_move(mv, &mut new_pos)?;
     }
+
+    *pos = new_pos;
+
+    Ok(())
 }
 
-pub fn go(pos: &mut Position, tt: &mut TranspositionTable, stopper: &Stopper) {
-    let reporter = UciReporter::new();
-    search(pos, tt, &reporter, stopper);
+pub fn go(pos: &mut Position, tt: &mut TranspositionTable, stopper: &Stopper, options: &Options, debug: bool) {
+    let reporter = UciReporter::new(pos, options, debug);
+    let params = search_params(options, debug);
+    let deterministic = options.check(options::DETERMINISTIC);
 
-    match reporter.best_move() {
+    // Start from a clean slate so that earlier searches can't have an effect.
+    if deterministic {
+        tt.clear();
+    }
+
+    match options.string(options::EVALUATOR) {
+        options::EVALUATOR_MATERIAL => {
+            let mut evaluator = MaterialEval::new(pos);
+            search_with_evaluator(pos, tt, &reporter, stopper, &params, &mut evaluator);
+        }
+        _ => search(pos, tt, &reporter, stopper, &params),
+    }
+
+    // At reduced strength the move is picked from the best few instead.
+    let best_move = match skill(options) {
+        Some(skill) => {
+            let seed = if deterministic {
+                pos.key | 1
+            } else {
+                SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64 | 1
+            };
+            let mv = skill.pick_move(&reporter.candidates(), &mut XorShift64::new(seed));
+            mv.map(UciMove::from).or(reporter.best_move())
+        }
+        None => reporter.best_move(),
+    };
+
+    match best_move {
         Some(mv) => println!("bestmove {mv}"),
         None => println!("bestmove (none)"),
     }
 }
 
-pub fn set_option(name: String, value: Option<String>, tt: &mut TranspositionTable) {
-    match name.as_str() {
-        "hash" => {
-            let size_mb = value.unwrap().parse().unwrap();
-            *tt = TranspositionTable::new(size_mb);
-        }
-        _ => panic!("unknown option '{name}'"),
+fn search_params(options: &Options, debug: bool) -> SearchParams {
+    // Contempt would skew the eval towards whichever side is to move, which
+    // isn't wanted when analysing both sides of a position.
+    let contempt = if options.check(options::ANALYSE_MODE) {
+        0
+    } else {
+        options.spin(options::CONTEMPT) as i32
+    };
+
+    let candidates = if skill(options).is_some() { skill::CANDIDATES } else { 1 };
+
+    // Recording the tree slows the search down a lot, so it's only done when
+    // a file has been given to write it to.
+    let tree = match options.string(options::TREE_FILE) {
+        "" => None,
+        _ => Some(TreeFilter {
+            max_ply: match options.spin(options::TREE_PLY) {
+                0 => None,
+                ply => Some(ply as u16),
+            },
+            root_move: options.string(options::TREE_MOVE).parse().ok(),
+        }),
+    };
+
+    SearchParams {
+        contempt,
+        candidates,
+        tree,
+        // Statistics are only shown in debug mode.
+        stats: debug,
+    }
+}
+
+pub fn skill(options: &Options) -> Option<Skill> {
+    if options.check(options::LIMIT_STRENGTH) {
+        Skill::from_elo(options.spin(options::ELO) as u32)
+    } else {
+        Skill::from_level(options.spin(options::SKILL_LEVEL) as u8)
+    }
+}
+
+pub fn go_mate(pos: &mut Position, moves: u8, stopper: &Stopper, options: &Options) {
+    let reporter = UciReporter::new(pos, options, false);
+
+    if !mate::search(pos, &reporter, stopper, moves) {
+        println!("info string no mate in {moves} found");
+    }
+
+    // Fall back to any legal move since a bestmove must always be sent.
+    match reporter
+        .best_move()
+        .or_else(|| generate_legal_moves(pos).first().map(|mv| (*mv).into()))
+    {
+        Some(mv) => println!("bestmove {mv}"),
+        None => println!("bestmove (none)"),
+    }
+}
+
+pub fn set_option(
+    name: String,
+    value: Option<String>,
+    options: &mut Options,
+    tt: &mut TranspositionTable,
+) -> Result<(), String> {
+    let option = options.set(&name, value.as_deref())?;
+
+    match option.name {
+        options::HASH => *tt = TranspositionTable::new(options.spin(options::HASH) as usize),
+        options::CLEAR_HASH => tt.clear(),
+        _ => (),
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::piece::Piece;
+    use crate::position::START_POS_FEN;
     use crate::square::Square;
+    use crate::testing::*;
     use crate::uci::command::UciCommand::{self, *};
 
     #[test]
@@ -114,7 +350,7 @@ mod tests {
         };
         let Ok(mut pos) = fen.parse() else { panic!() };
 
-        position(fen, moves, &mut pos);
+        assert!(position(fen, moves, &mut pos).is_ok());
 
         assert_eq!(pos.board.piece_at(Square::E4), Some(Piece::WP));
         assert_eq!(pos.board.piece_at(Square::E5), Some(Piece::BP));
@@ -128,7 +364,7 @@ mod tests {
         };
         let Ok(mut pos) = fen.parse() else { panic!() };
 
-        position(fen, moves, &mut pos);
+        assert!(position(fen, moves, &mut pos).is_ok());
 
         assert_eq!(pos.board.piece_at(Square::B8), Some(Piece::WQ));
         assert_eq!(pos.board.piece_at(Square::G1), Some(Piece::BR));
@@ -142,7 +378,7 @@ mod tests {
         };
         let Ok(mut pos) = fen.parse() else { panic!() };
 
-        position(fen, moves, &mut pos);
+        assert!(position(fen, moves, &mut pos).is_ok());
 
         assert_eq!(pos.board.piece_at(Square::D6), Some(Piece::WP));
         assert!(!pos.board.has_piece_at(Square::E5));
@@ -157,13 +393,92 @@ mod tests {
         };
         let Ok(mut pos) = fen.parse() else { panic!() };
 
-        position(fen, moves, &mut pos);
+        assert!(position(fen, moves, &mut pos).is_ok());
 
         assert_eq!(pos.board.piece_at(Square::E3), Some(Piece::BP));
         assert!(!pos.board.has_piece_at(Square::D4));
         assert!(!pos.board.has_piece_at(Square::E4));
     }
 
+    #[test]
+    fn handle_position_command_with_illegal_move() {
+        let mut pos = parse_fen(START_POS_FEN);
+        let command = "position startpos moves e2e4 e7e5 e1e3";
+        let Position(fen, moves) = parse_command(command) else {
+            panic!()
+        };
+
+        assert_eq!(position(fen, moves, &mut pos), Err("illegal move 'e1e3'".to_string()));
+        assert_eq!(pos.to_fen(), START_POS_FEN);
+    }
+
+    #[test]
+    fn handle_position_command_with_unplayable_position() {
+        let mut pos = parse_fen(START_POS_FEN);
+
+        assert_eq!(
+            position("8/8/8/8/8/8/8/4K3 w - - 0 1".to_string(), vec![], &mut pos),
+            Err("each side must have exactly one king".to_string())
+        );
+        assert_eq!(pos.to_fen(), START_POS_FEN);
+    }
+
+    #[test]
+    fn handle_domove_command_with_illegal_move() {
+        let mut pos = parse_fen(START_POS_FEN);
+        let DoMove(mv) = parse_command("domove e2e5") else {
+            panic!()
+        };
+
+        assert_eq!(do_move(mv, &mut pos), Err("illegal move 'e2e5'".to_string()));
+        assert_eq!(pos.to_fen(), START_POS_FEN);
+    }
+
+    #[test]
+    fn handle_domove_command_with_missing_piece() {
+        let mut pos = parse_fen(START_POS_FEN);
+        let DoMove(mv) = parse_command("domove e4e5") else {
+            panic!()
+        };
+
+        assert!(do_move(mv, &mut pos).is_err());
+    }
+
+    #[test]
+    fn count_the_root_as_the_only_node_at_depth_zero() {
+        let mated = parse_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1");
+
+        assert_eq!(total_nodes(0, &perft::divide(&mated, 0, 1, 0)), 1);
+        assert_eq!(total_nodes(1, &perft::divide(&mated, 1, 1, 0)), 0);
+    }
+
+    #[test]
+    fn ignore_contempt_when_analysing() {
+        let mut options = Options::new();
+        options.set(options::CONTEMPT, Some("20")).unwrap();
+
+        assert_eq!(search_params(&options, false).contempt, 20);
+
+        options.set(options::ANALYSE_MODE, Some("true")).unwrap();
+
+        assert_eq!(search_params(&options, false).contempt, 0);
+    }
+
+    #[test]
+    fn only_record_the_tree_when_given_a_file() {
+        let mut options = Options::new();
+
+        assert!(search_params(&options, false).tree.is_none());
+
+        options.set(options::TREE_FILE, Some("/tmp/tree.txt")).unwrap();
+        options.set(options::TREE_PLY, Some("3")).unwrap();
+        options.set(options::TREE_MOVE, Some("e2e4")).unwrap();
+
+        let tree = search_params(&options, false).tree.unwrap();
+        assert_eq!(tree.max_ply, Some(3));
+        assert_eq!(tree.root_move, Some("e2e4".parse().unwrap()));
+    }
+
     fn parse_command(str: &str) -> UciCommand {
         let command = str.parse();
         assert!(command.is_ok());
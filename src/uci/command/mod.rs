@@ -1,4 +1,5 @@
 use super::r#move::UciMove;
+use crate::movegen::perft;
 use std::time::Duration;
 
 pub mod handle;
@@ -8,10 +9,14 @@ mod parse;
 pub enum UciCommand {
     Init,
     IsReady,
+    Debug(bool),
     NewGame,
     PrintBoard,
     PrintFen,
-    Perft(u8),
+    Eval,
+    Perft(PerftParams),
+    PerftSuite(String, Option<u8>),
+    Solve(usize),
     DoMove(UciMove),
     Position(String, Vec<UciMove>),
     Go(GoParams),
@@ -28,7 +33,9 @@ pub struct GoParams {
     pub btime: Option<Duration>,
     pub winc: Option<Duration>,
     pub binc: Option<Duration>,
+    pub movestogo: Option<u8>,
     pub nodes: Option<u128>,
+    pub mate: Option<u8>,
 }
 
 impl GoParams {
@@ -40,7 +47,29 @@ impl GoParams {
             btime: None,
             winc: None,
             binc: None,
+            movestogo: None,
             nodes: None,
+            mate: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PerftParams {
+    pub depth: u8,
+    // Defaults to the number of available cores.
+    pub threads: Option<usize>,
+    pub hash_mb: usize,
+    pub stats: bool,
+}
+
+impl PerftParams {
+    fn new(depth: u8) -> Self {
+        Self {
+            depth,
+            threads: None,
+            hash_mb: perft::DEFAULT_HASH_MB,
+            stats: false,
         }
     }
 }
@@ -1,9 +1,10 @@
 use super::{
-    GoParams,
+    GoParams, PerftParams,
     UciCommand::{self, *},
 };
 use crate::position::{Position, START_POS_FEN};
-use crate::search::tt;
+use crate::search::{mate::MAX_MATE_MOVES, tt};
+use crate::uci::options;
 use std::time::Duration;
 
 impl std::str::FromStr for UciCommand {
@@ -11,26 +12,42 @@ impl std::str::FromStr for UciCommand {
 
     fn from_str(command: &str) -> Result<Self, Self::Err> {
         let parts: Vec<_> = command.split_whitespace().collect();
-        let args = &parts[1..];
 
-        match parts[0] {
+        let Some((&name, args)) = parts.split_first() else {
+            return Err("empty command".to_string());
+        };
+
+        match name {
             "uci" => Ok(Init),
             "isready" => Ok(IsReady),
+            "debug" => Ok(parse_debug(args)?),
             "ucinewgame" => Ok(NewGame),
             "printboard" => Ok(PrintBoard),
             "printfen" => Ok(PrintFen),
+            "eval" => Ok(Eval),
             "perft" => Ok(parse_perft(args)?),
+            "perftsuite" => Ok(parse_perft_suite(args)?),
+            "solve" => Ok(parse_solve(args)?),
             "domove" => Ok(parse_domove(args)?),
             "position" => Ok(parse_position(args)?),
             "go" => Ok(parse_go(args)?),
             "setoption" => Ok(parse_setoption(args)?),
             "stop" => Ok(Stop),
             "quit" => Ok(Quit),
-            _ => Err(format!("unknown command '{}'", parts[0])),
+            _ => Err(format!("unknown command '{name}'")),
         }
     }
 }
 
+fn parse_debug(args: &[&str]) -> Result<UciCommand, String> {
+    match args.first() {
+        Some(&"on") => Ok(Debug(true)),
+        Some(&"off") => Ok(Debug(false)),
+        Some(value) => Err(format!("invalid value '{value}' for debug")),
+        None => Err("missing value for debug".to_string()),
+    }
+}
+
 fn parse_perft(args: &[&str]) -> Result<UciCommand, String> {
     if args.is_empty() {
         return Err("missing depth".to_string());
@@ -40,7 +57,62 @@ fn parse_perft(args: &[&str]) -> Result<UciCommand, String> {
         .parse()
         .map_err(|_| "could not parse value for depth".to_string())?;
 
-    Ok(Perft(depth))
+    let mut params = PerftParams::new(depth);
+    let mut iter = args[1..].iter();
+
+    while let Some(attr) = iter.next() {
+        if *attr == "stats" {
+            params.stats = true;
+            continue;
+        }
+
+        let Some(value) = iter.next() else {
+            return Err(format!("missing value for '{attr}' attribute"));
+        };
+
+        let value = value
+            .parse()
+            .map_err(|_| format!("could not parse value for '{attr}' attribute"))?;
+
+        match *attr {
+            "threads" if value > 0 => params.threads = Some(value),
+            "threads" => return Err("invalid value for 'threads' attribute".to_string()),
+            "hash" if value <= tt::MAX_SIZE_MB => params.hash_mb = value,
+            "hash" => return Err("invalid value for 'hash' attribute".to_string()),
+            _ => return Err(format!("unknown attribute '{attr}'")),
+        }
+    }
+
+    Ok(Perft(params))
+}
+
+fn parse_perft_suite(args: &[&str]) -> Result<UciCommand, String> {
+    let Some(path) = args.first() else {
+        return Err("missing file".to_string());
+    };
+
+    let max_depth = match args.get(1) {
+        Some(depth) => Some(
+            depth
+                .parse()
+                .map_err(|_| "could not parse value for max depth".to_string())?,
+        ),
+        None => None,
+    };
+
+    Ok(PerftSuite(path.to_string(), max_depth))
+}
+
+fn parse_solve(args: &[&str]) -> Result<UciCommand, String> {
+    if args.is_empty() {
+        return Err("missing max nodes".to_string());
+    }
+
+    let max_nodes = args[0]
+        .parse()
+        .map_err(|_| "could not parse value for max nodes".to_string())?;
+
+    Ok(Solve(max_nodes))
 }
 
 fn parse_domove(args: &[&str]) -> Result<UciCommand, String> {
@@ -101,7 +173,9 @@ fn parse_go(args: &[&str]) -> Result<UciCommand, String> {
             "btime" => params.btime = Some(parse_duration_attr("btime", value)?),
             "winc" => params.winc = Some(parse_duration_attr("winc", value)?),
             "binc" => params.binc = Some(parse_duration_attr("binc", value)?),
+            "movestogo" => params.movestogo = Some(parse_u8_attr("movestogo", value)?),
             "nodes" => params.nodes = Some(parse_u128_attr("nodes", value)?),
+            "mate" => params.mate = Some(parse_mate_attr(value)?),
             _ => return Err(format!("unknown attribute '{attr}'")),
         }
     }
@@ -132,27 +206,20 @@ fn parse_setoption(args: &[&str]) -> Result<UciCommand, String> {
     }
 
     let name = name_parts.join(" ").trim().to_string().to_lowercase();
-    let value = value_parts.join(" ").trim().to_string().to_lowercase();
+    let value = value_parts.join(" ").trim().to_string();
 
     if name.is_empty() {
         return Err("missing option name".to_string());
     }
 
-    match name.as_str() {
-        "hash" => {
-            if value.is_empty() {
-                return Err("missing value for 'hash' option".to_string());
-            };
-            let Ok(size_mb) = value.parse::<usize>() else {
-                return Err("could not parse value for 'hash' option".to_string());
-            };
-            if !(tt::MIN_SIZE_MB..=tt::MAX_SIZE_MB).contains(&size_mb) {
-                return Err("invalid value for 'hash' option".to_string());
-            };
-            Ok(SetOption(name, Some(value)))
-        }
-        _ => Err(format!("unknown option '{name}'")),
-    }
+    let Some(option) = options::find(&name) else {
+        return Err(format!("unknown option '{name}'"));
+    };
+
+    let value = if value.is_empty() { None } else { Some(value) };
+    option.parse_value(value.as_deref())?;
+
+    Ok(SetOption(name, value))
 }
 
 fn parse_u8_attr(attr: &str, value: &str) -> Result<u8, String> {
@@ -161,6 +228,13 @@ fn parse_u8_attr(attr: &str, value: &str) -> Result<u8, String> {
         .map_err(|_| format!("invalid value for '{attr}' attribute"))
 }
 
+fn parse_mate_attr(value: &str) -> Result<u8, String> {
+    match value.parse::<u8>() {
+        Ok(moves) if (1..=MAX_MATE_MOVES).contains(&moves) => Ok(moves),
+        _ => Err("invalid value for 'mate' attribute".to_string()),
+    }
+}
+
 fn parse_u128_attr(attr: &str, value: &str) -> Result<u128, String> {
     value
         .parse::<u128>()
@@ -182,6 +256,27 @@ mod tests {
     use crate::square::Square;
     use crate::uci::r#move::UciMove;
 
+    #[test]
+    fn parse_empty_command() {
+        assert_eq!("".parse::<UciCommand>(), Err("empty command".to_string()));
+        assert_eq!(" \t ".parse::<UciCommand>(), Err("empty command".to_string()));
+    }
+
+    #[test]
+    fn parse_unknown_command() {
+        assert_eq!(
+            "foo bar".parse::<UciCommand>(),
+            Err("unknown command 'foo'".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_position_command_with_invalid_fen() {
+        assert!("position fen 8/8/8/8/8/8/8/8 w - - x 1".parse::<UciCommand>().is_err());
+        assert!("position fen".parse::<UciCommand>().is_err());
+        assert!("position startpos moves e2é4".parse::<UciCommand>().is_err());
+    }
+
     #[test]
     fn parse_init_command() {
         assert_eq!("uci".parse(), Ok(Init));
@@ -197,6 +292,14 @@ mod tests {
         assert_eq!("ucinewgame".parse(), Ok(NewGame));
     }
 
+    #[test]
+    fn parse_debug_command() {
+        assert_eq!("debug on".parse(), Ok(Debug(true)));
+        assert_eq!("debug off".parse(), Ok(Debug(false)));
+        assert!("debug".parse::<UciCommand>().is_err());
+        assert!("debug maybe".parse::<UciCommand>().is_err());
+    }
+
     #[test]
     fn parse_printboard_command() {
         assert_eq!("printboard".parse(), Ok(PrintBoard));
@@ -207,9 +310,49 @@ mod tests {
         assert_eq!("printfen".parse(), Ok(PrintFen));
     }
 
+    #[test]
+    fn parse_eval_command() {
+        assert_eq!("eval".parse(), Ok(Eval));
+    }
+
     #[test]
     fn parse_perft_command() {
-        assert_eq!("perft 1".parse(), Ok(Perft(1)));
+        assert_eq!("perft 1".parse(), Ok(Perft(PerftParams::new(1))));
+    }
+
+    #[test]
+    fn parse_perft_command_with_attributes() {
+        assert_eq!(
+            "perft 6 threads 8 hash 256 stats".parse(),
+            Ok(Perft(PerftParams {
+                depth: 6,
+                threads: Some(8),
+                hash_mb: 256,
+                stats: true,
+            }))
+        );
+        assert!("perft 6 threads 0".parse::<UciCommand>().is_err());
+        assert!("perft 6 hash".parse::<UciCommand>().is_err());
+        assert!("perft 6 hash 99999999999999".parse::<UciCommand>().is_err());
+    }
+
+    #[test]
+    fn parse_perftsuite_command() {
+        assert_eq!(
+            "perftsuite perftsuite.epd".parse(),
+            Ok(PerftSuite("perftsuite.epd".to_string(), None))
+        );
+        assert_eq!(
+            "perftsuite perftsuite.epd 4".parse(),
+            Ok(PerftSuite("perftsuite.epd".to_string(), Some(4)))
+        );
+        assert!("perftsuite".parse::<UciCommand>().is_err());
+    }
+
+    #[test]
+    fn parse_solve_command() {
+        assert_eq!("solve 100000".parse(), Ok(Solve(100_000)));
+        assert!("solve".parse::<UciCommand>().is_err());
     }
 
     #[test]
@@ -289,7 +432,7 @@ mod tests {
     #[test]
     fn parse_go_command() {
         assert_eq!(
-            "go depth 1 movetime 2 wtime 3 btime 4 winc 5 binc 6 nodes 7".parse(),
+            "go depth 1 movetime 2 wtime 3 btime 4 winc 5 binc 6 movestogo 7 nodes 8".parse(),
             Ok(Go(GoParams {
                 depth: Some(1),
                 movetime: Some(Duration::from_millis(2)),
@@ -297,7 +440,9 @@ mod tests {
                 btime: Some(Duration::from_millis(4)),
                 winc: Some(Duration::from_millis(5)),
                 binc: Some(Duration::from_millis(6)),
-                nodes: Some(7),
+                movestogo: Some(7),
+                nodes: Some(8),
+                mate: None,
             }))
         );
     }
@@ -313,11 +458,25 @@ mod tests {
                 btime: None,
                 winc: None,
                 binc: None,
+                movestogo: None,
                 nodes: None,
+                mate: None,
             }))
         );
     }
 
+    #[test]
+    fn parse_go_command_with_mate_attribute() {
+        let Ok(Go(params)) = "go mate 3".parse() else { panic!() };
+        assert_eq!(params.mate, Some(3));
+
+        assert_eq!(
+            "go mate 0".parse::<UciCommand>(),
+            Err("invalid value for 'mate' attribute".to_string())
+        );
+        assert!("go mate 200".parse::<UciCommand>().is_err());
+    }
+
     #[test]
     fn parse_setoption_command_with_hash_option() {
         assert_eq!(
@@ -326,6 +485,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_setoption_command_with_button_option() {
+        assert_eq!(
+            "setoption name Clear Hash".parse(),
+            Ok(SetOption("clear hash".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn parse_setoption_command_with_check_option() {
+        assert_eq!(
+            "setoption name UCI_ShowWDL value true".parse(),
+            Ok(SetOption("uci_showwdl".to_string(), Some("true".to_string())))
+        );
+    }
+
+    #[test]
+    fn parse_setoption_command_with_invalid_value() {
+        assert_eq!(
+            "setoption name Hash value 0".parse::<UciCommand>(),
+            Err("invalid value for 'Hash' option".to_string())
+        );
+        assert_eq!(
+            "setoption name Move Overhead".parse::<UciCommand>(),
+            Err("missing value for 'Move Overhead' option".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_setoption_command_with_unknown_option() {
+        assert_eq!(
+            "setoption name Threads value 4".parse::<UciCommand>(),
+            Err("unknown option 'threads'".to_string())
+        );
+    }
+
     #[test]
     fn parse_stop_command() {
         assert_eq!("stop".parse(), Ok(Stop));
@@ -1,6 +1,7 @@
 use self::command::{UciCommand::*, handle};
+use self::options::Options;
 use crate::colour::Colour;
-use crate::eval::EVAL_MATE_THRESHOLD;
+use crate::eval::{EVAL_MATE_THRESHOLD, phase::phase};
 use crate::position::Position;
 use crate::search::{
     stopper::Stopper,
@@ -15,10 +16,12 @@ use std::{
         mpsc,
     },
     thread,
+    time::Duration,
 };
 
 pub mod command;
 pub mod r#move;
+pub mod options;
 
 mod reporter;
 
@@ -27,11 +30,25 @@ pub fn main() {
     let stop_signal = Arc::new(AtomicBool::new(false));
     let pos = Arc::new(Mutex::new(Position::startpos()));
     let tt = Arc::new(Mutex::new(TranspositionTable::new(tt::DEFAULT_SIZE_MB)));
+    let mut options = Options::new();
+    let mut debug = false;
+    let mut search_thread: Option<thread::JoinHandle<()>> = None;
 
     thread::spawn(move || {
         loop {
             let mut buffer = String::new();
-            io::stdin().read_line(&mut buffer).unwrap();
+
+            match io::stdin().read_line(&mut buffer) {
+                // Input has been closed so drop the sender, letting the main
+                // loop know that no more commands will arrive.
+                Ok(0) => break,
+                Ok(_) => (),
+                Err(err) => {
+                    println!("info string error: {err}");
+                    continue;
+                }
+            }
+
             let command = buffer.trim();
 
             if command.is_empty() {
@@ -40,37 +57,67 @@ pub fn main() {
 
             match command.parse() {
                 Ok(parsed) => uci_tx.send(parsed).unwrap(),
-                Err(err) => println!("error: {err}"),
+                Err(err) => println!("info string error: {err}"),
             }
         }
     });
 
     loop {
-        match uci_rx.recv().unwrap() {
+        let Ok(command) = uci_rx.recv() else {
+            // Let any search in progress report its best move before exiting.
+            if let Some(search_thread) = search_thread {
+                search_thread.join().unwrap();
+            }
+            break;
+        };
+
+        match command {
             Init => handle::init(),
             IsReady => handle::is_ready(),
+            Debug(on) => debug = on,
             NewGame => handle::new_game(&mut pos.lock().unwrap(), &mut tt.lock().unwrap()),
             PrintBoard => handle::print_board(&pos.lock().unwrap()),
             PrintFen => handle::print_fen(&pos.lock().unwrap()),
-            Perft(depth) => handle::perft(depth, &pos.lock().unwrap()),
-            DoMove(mv) => handle::do_move(mv, &mut pos.lock().unwrap()),
-            Position(fen, moves) => handle::position(fen, moves, &mut pos.lock().unwrap()),
+            Eval => handle::eval(&pos.lock().unwrap()),
+            Perft(params) => handle::perft(params, &pos.lock().unwrap()),
+            PerftSuite(path, max_depth) => handle::perft_suite(&path, max_depth),
+            Solve(max_nodes) => handle::solve(max_nodes, &pos.lock().unwrap()),
+            DoMove(mv) => {
+                if let Err(err) = handle::do_move(mv, &mut pos.lock().unwrap()) {
+                    println!("info string error: {err}");
+                }
+            }
+            Position(fen, moves) => {
+                if let Err(err) = handle::position(fen, moves, &mut pos.lock().unwrap()) {
+                    println!("info string error: {err}");
+                }
+            }
             Go(params) => {
                 let stop_signal = Arc::clone(&stop_signal);
-                let pos = Arc::clone(&pos);
                 let tt = Arc::clone(&tt);
+                let move_overhead = Duration::from_millis(options.spin(options::MOVE_OVERHEAD) as u64);
+                let options = options.clone();
 
-                thread::spawn(move || {
-                    stop_signal.store(false, Ordering::Relaxed);
+                // Take a copy of the position and reset the stop signal before
+                // starting the search so that commands which follow can't
+                // affect it, depending on when the thread gets to run. The
+                // copy also means searching doesn't block this thread.
+                let mut pos = pos.lock().unwrap().clone();
+                stop_signal.store(false, Ordering::Relaxed);
 
+                search_thread = Some(thread::spawn(move || {
                     let mut stopper = Stopper::new();
                     stopper.at_signal(&stop_signal);
                     stopper.at_depth(params.depth);
                     stopper.at_nodes(params.nodes);
 
-                    let time = params.movetime.map(TimeLimit::fixed).or_else(|| {
-                        let pos = pos.lock().unwrap();
+                    // Weaker play also comes from searching less.
+                    if let Some(skill) = handle::skill(&options) {
+                        stopper.at_depth(Some(params.depth.map_or(skill.depth(), |d| d.min(skill.depth()))));
+                        stopper.at_nodes(Some(params.nodes.map_or(skill.nodes(), |n| n.min(skill.nodes()))));
+                    }
 
+                    let time = params.movetime.map(|t| TimeLimit::fixed(t, move_overhead)).or_else(|| {
                         let (time_left, time_inc) = match pos.colour_to_move {
                             Colour::White => (params.wtime, params.winc),
                             _ => (params.btime, params.binc),
@@ -81,17 +128,29 @@ pub fn main() {
                             stopper.at_eval(Some(EVAL_MATE_THRESHOLD));
                         }
 
-                        time_left.map(|t| TimeLimit::dynamic(t, time_inc))
+                        let phase = phase(&pos.board);
+
+                        time_left.map(|t| TimeLimit::dynamic(t, time_inc, params.movestogo, move_overhead, phase))
                     });
                     stopper.at_time(time);
 
-                    // Clone the position so that searching doesn't block
-                    // this thread and we can still handle other commands.
-                    let mut pos = pos.lock().unwrap().clone();
-                    handle::go(&mut pos, &mut tt.lock().unwrap(), &stopper);
-                });
+                    // Time limits are converted to nodes so that the same
+                    // commands always lead to the same search.
+                    if options.check(options::DETERMINISTIC) {
+                        stopper.at_nps(Some(options.spin(options::DETERMINISTIC_NPS) as u128));
+                    }
+
+                    match params.mate {
+                        Some(moves) => handle::go_mate(&mut pos, moves, &stopper, &options),
+                        None => handle::go(&mut pos, &mut tt.lock().unwrap(), &stopper, &options, debug),
+                    }
+                }));
+            }
+            SetOption(name, value) => {
+                if let Err(err) = handle::set_option(name, value, &mut options, &mut tt.lock().unwrap()) {
+                    println!("info string error: {err}");
+                }
             }
-            SetOption(name, value) => handle::set_option(name, value, &mut tt.lock().unwrap()),
             Stop => stop_signal.store(true, Ordering::Relaxed),
             Quit => break,
         }
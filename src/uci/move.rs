@@ -24,7 +24,7 @@ impl std::str::FromStr for UciMove {
     type Err = String;
 
     fn from_str(mv: &str) -> Result<Self, Self::Err> {
-        if mv.len() != 4 && mv.len() != 5 {
+        if !mv.is_ascii() || (mv.len() != 4 && mv.len() != 5) {
             return Err("invalid UCI move".to_string());
         }
 
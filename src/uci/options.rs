@@ -0,0 +1,424 @@
+use crate::search::{MAX_PLY, skill, tt};
+
+pub const HASH: &str = "Hash";
+pub const CLEAR_HASH: &str = "Clear Hash";
+pub const MOVE_OVERHEAD: &str = "Move Overhead";
+pub const CURRMOVE_DELAY: &str = "Currmove Delay";
+pub const SHOW_WDL: &str = "UCI_ShowWDL";
+pub const ANALYSE_MODE: &str = "UCI_AnalyseMode";
+pub const CONTEMPT: &str = "Contempt";
+pub const SKILL_LEVEL: &str = "Skill Level";
+pub const LIMIT_STRENGTH: &str = "UCI_LimitStrength";
+pub const ELO: &str = "UCI_Elo";
+pub const DETERMINISTIC: &str = "Deterministic";
+pub const DETERMINISTIC_NPS: &str = "Deterministic NPS";
+pub const EVALUATOR: &str = "Evaluator";
+pub const TREE_FILE: &str = "Tree File";
+pub const TREE_PLY: &str = "Tree Ply";
+pub const TREE_MOVE: &str = "Tree Move";
+
+pub const EVALUATOR_HANDCRAFTED: &str = "Handcrafted";
+pub const EVALUATOR_MATERIAL: &str = "Material";
+
+pub struct UciOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+}
+
+pub enum OptionKind {
+    Spin {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    Check {
+        default: bool,
+    },
+    Combo {
+        default: &'static str,
+        vars: &'static [&'static str],
+    },
+    String {
+        default: &'static str,
+    },
+    Button,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    Spin(i64),
+    Check(bool),
+    Combo(&'static str),
+    String(String),
+    Button,
+}
+
+pub static OPTIONS: &[UciOption] = &[
+    UciOption {
+        name: HASH,
+        kind: OptionKind::Spin {
+            default: tt::DEFAULT_SIZE_MB as i64,
+            min: tt::MIN_SIZE_MB as i64,
+            max: tt::MAX_SIZE_MB as i64,
+        },
+    },
+    UciOption {
+        name: CLEAR_HASH,
+        kind: OptionKind::Button,
+    },
+    UciOption {
+        name: MOVE_OVERHEAD,
+        kind: OptionKind::Spin {
+            default: 10,
+            min: 0,
+            max: 5000,
+        },
+    },
+    UciOption {
+        name: CURRMOVE_DELAY,
+        kind: OptionKind::Spin {
+            default: 3000,
+            min: 0,
+            max: 60000,
+        },
+    },
+    UciOption {
+        name: SHOW_WDL,
+        kind: OptionKind::Check { default: false },
+    },
+    UciOption {
+        name: ANALYSE_MODE,
+        kind: OptionKind::Check { default: false },
+    },
+    UciOption {
+        name: CONTEMPT,
+        kind: OptionKind::Spin {
+            default: 0,
+            min: -100,
+            max: 100,
+        },
+    },
+    UciOption {
+        name: SKILL_LEVEL,
+        kind: OptionKind::Spin {
+            default: skill::MAX_LEVEL as i64,
+            min: 0,
+            max: skill::MAX_LEVEL as i64,
+        },
+    },
+    UciOption {
+        name: LIMIT_STRENGTH,
+        kind: OptionKind::Check { default: false },
+    },
+    UciOption {
+        name: ELO,
+        kind: OptionKind::Spin {
+            default: skill::MAX_ELO as i64,
+            min: skill::MIN_ELO as i64,
+            max: skill::MAX_ELO as i64,
+        },
+    },
+    UciOption {
+        name: DETERMINISTIC,
+        kind: OptionKind::Check { default: false },
+    },
+    UciOption {
+        name: DETERMINISTIC_NPS,
+        kind: OptionKind::Spin {
+            default: 500_000,
+            min: 1_000,
+            max: 100_000_000,
+        },
+    },
+    UciOption {
+        name: EVALUATOR,
+        kind: OptionKind::Combo {
+            default: EVALUATOR_HANDCRAFTED,
+            vars: &[EVALUATOR_HANDCRAFTED, EVALUATOR_MATERIAL],
+        },
+    },
+    UciOption {
+        name: TREE_FILE,
+        kind: OptionKind::String { default: "" },
+    },
+    UciOption {
+        name: TREE_PLY,
+        kind: OptionKind::Spin {
+            default: 0,
+            min: 0,
+            max: MAX_PLY as i64,
+        },
+    },
+    UciOption {
+        name: TREE_MOVE,
+        kind: OptionKind::String { default: "" },
+    },
+];
+
+// Option names are matched case-insensitively, as required by the UCI protocol.
+pub fn find(name: &str) -> Option<&'static UciOption> {
+    index(name).map(|index| &OPTIONS[index])
+}
+
+fn index(name: &str) -> Option<usize> {
+    OPTIONS.iter().position(|option| option.name.eq_ignore_ascii_case(name))
+}
+
+impl UciOption {
+    pub fn default_value(&self) -> OptionValue {
+        match self.kind {
+            OptionKind::Spin { default, .. } => OptionValue::Spin(default),
+            OptionKind::Check { default } => OptionValue::Check(default),
+            OptionKind::Combo { default, .. } => OptionValue::Combo(default),
+            OptionKind::String { default } => OptionValue::String(default.to_string()),
+            OptionKind::Button => OptionValue::Button,
+        }
+    }
+
+    pub fn parse_value(&self, value: Option<&str>) -> Result<OptionValue, String> {
+        let name = self.name;
+
+        if let OptionKind::Button = self.kind {
+            return Ok(OptionValue::Button);
+        }
+
+        // Strings can be cleared by sending no value, or "<empty>" as shown
+        // for empty defaults.
+        if let OptionKind::String { .. } = self.kind {
+            let value = value.filter(|value| *value != "<empty>").unwrap_or_default();
+            return Ok(OptionValue::String(value.to_string()));
+        }
+
+        let Some(value) = value.filter(|value| !value.is_empty()) else {
+            return Err(format!("missing value for '{name}' option"));
+        };
+
+        match self.kind {
+            OptionKind::Spin { min, max, .. } => {
+                let Ok(value) = value.parse::<i64>() else {
+                    return Err(format!("could not parse value for '{name}' option"));
+                };
+                if !(min..=max).contains(&value) {
+                    return Err(format!("invalid value for '{name}' option"));
+                }
+                Ok(OptionValue::Spin(value))
+            }
+            OptionKind::Check { .. } => match value.to_lowercase().as_str() {
+                "true" => Ok(OptionValue::Check(true)),
+                "false" => Ok(OptionValue::Check(false)),
+                _ => Err(format!("could not parse value for '{name}' option")),
+            },
+            OptionKind::Combo { vars, .. } => match vars.iter().find(|var| var.eq_ignore_ascii_case(value)) {
+                Some(var) => Ok(OptionValue::Combo(var)),
+                None => Err(format!("invalid value for '{name}' option")),
+            },
+            OptionKind::String { .. } | OptionKind::Button => unreachable!(),
+        }
+    }
+}
+
+impl std::fmt::Display for UciOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+
+        match self.kind {
+            OptionKind::Spin { default, min, max } => write!(f, "spin default {default} min {min} max {max}"),
+            OptionKind::Check { default } => write!(f, "check default {default}"),
+            OptionKind::Combo { default, vars } => {
+                write!(f, "combo default {default}")?;
+                vars.iter().try_for_each(|var| write!(f, " var {var}"))
+            }
+            OptionKind::String { default: "" } => write!(f, "string default <empty>"),
+            OptionKind::String { default } => write!(f, "string default {default}"),
+            OptionKind::Button => write!(f, "button"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    values: Vec<OptionValue>,
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Self {
+            values: OPTIONS.iter().map(UciOption::default_value).collect(),
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: Option<&str>) -> Result<&'static UciOption, String> {
+        let index = index(name).ok_or_else(|| format!("unknown option '{name}'"))?;
+        let option = &OPTIONS[index];
+
+        self.values[index] = option.parse_value(value)?;
+
+        Ok(option)
+    }
+
+    pub fn spin(&self, name: &str) -> i64 {
+        match self.value(name) {
+            OptionValue::Spin(value) => *value,
+            _ => panic!("option '{name}' is not a spin"),
+        }
+    }
+
+    pub fn check(&self, name: &str) -> bool {
+        match self.value(name) {
+            OptionValue::Check(value) => *value,
+            _ => panic!("option '{name}' is not a check"),
+        }
+    }
+
+    pub fn string(&self, name: &str) -> &str {
+        match self.value(name) {
+            OptionValue::Combo(value) => value,
+            OptionValue::String(value) => value,
+            _ => panic!("option '{name}' is not a combo or string"),
+        }
+    }
+
+    fn value(&self, name: &str) -> &OptionValue {
+        match index(name) {
+            Some(index) => &self.values[index],
+            None => panic!("unknown option '{name}'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_start_with_their_default_values() {
+        let options = Options::new();
+
+        assert_eq!(options.spin(HASH), tt::DEFAULT_SIZE_MB as i64);
+        assert_eq!(options.spin(MOVE_OVERHEAD), 10);
+        assert!(!options.check(SHOW_WDL));
+        assert!(!options.check(ANALYSE_MODE));
+        assert_eq!(options.string(EVALUATOR), EVALUATOR_HANDCRAFTED);
+    }
+
+    #[test]
+    fn set_an_option_by_case_insensitive_name() {
+        let mut options = Options::new();
+
+        assert!(options.set("move overhead", Some("250")).is_ok());
+        assert!(options.set("uci_showwdl", Some("true")).is_ok());
+        assert!(options.set("evaluator", Some("material")).is_ok());
+
+        assert_eq!(options.spin(MOVE_OVERHEAD), 250);
+        assert!(options.check(SHOW_WDL));
+        assert_eq!(options.string(EVALUATOR), EVALUATOR_MATERIAL);
+    }
+
+    #[test]
+    fn reject_spin_values_out_of_bounds() {
+        let mut options = Options::new();
+
+        assert_eq!(
+            options.set(HASH, Some("0")).err(),
+            Some("invalid value for 'Hash' option".to_string())
+        );
+        assert!(options.set(HASH, Some("abc")).is_err());
+        assert_eq!(options.spin(HASH), tt::DEFAULT_SIZE_MB as i64);
+    }
+
+    #[test]
+    fn reject_invalid_check_values() {
+        let mut options = Options::new();
+
+        assert!(options.set(SHOW_WDL, Some("yes")).is_err());
+        assert!(options.set(SHOW_WDL, None).is_err());
+    }
+
+    #[test]
+    fn parse_combo_and_string_values() {
+        let combo = UciOption {
+            name: "Style",
+            kind: OptionKind::Combo {
+                default: "Normal",
+                vars: &["Solid", "Normal", "Risky"],
+            },
+        };
+        let string = UciOption {
+            name: "Book File",
+            kind: OptionKind::String { default: "" },
+        };
+
+        assert_eq!(combo.parse_value(Some("risky")), Ok(OptionValue::Combo("Risky")));
+        assert!(combo.parse_value(Some("Reckless")).is_err());
+        assert_eq!(
+            string.parse_value(Some("/tmp/Book.bin")),
+            Ok(OptionValue::String("/tmp/Book.bin".to_string()))
+        );
+    }
+
+    #[test]
+    fn clear_string_values() {
+        let mut options = Options::new();
+
+        options.set(TREE_FILE, Some("/tmp/tree.txt")).unwrap();
+        options.set(TREE_FILE, Some("<empty>")).unwrap();
+        assert_eq!(options.string(TREE_FILE), "");
+
+        options.set(TREE_FILE, Some("/tmp/tree.txt")).unwrap();
+        options.set(TREE_FILE, None).unwrap();
+        assert_eq!(options.string(TREE_FILE), "");
+    }
+
+    #[test]
+    fn buttons_do_not_need_a_value() {
+        let mut options = Options::new();
+
+        assert!(options.set(CLEAR_HASH, None).is_ok());
+    }
+
+    #[test]
+    fn reject_unknown_options() {
+        let mut options = Options::new();
+
+        assert!(options.set("Threads", Some("4")).is_err());
+        assert!(find("Threads").is_none());
+    }
+
+    #[test]
+    fn display_options_in_uci_format() {
+        let combo = UciOption {
+            name: "Style",
+            kind: OptionKind::Combo {
+                default: "Normal",
+                vars: &["Solid", "Normal"],
+            },
+        };
+        let string = UciOption {
+            name: "Book File",
+            kind: OptionKind::String { default: "" },
+        };
+
+        assert_eq!(
+            find(HASH).unwrap().to_string(),
+            format!(
+                "option name Hash type spin default {} min {} max {}",
+                tt::DEFAULT_SIZE_MB,
+                tt::MIN_SIZE_MB,
+                tt::MAX_SIZE_MB
+            )
+        );
+        assert_eq!(
+            find(CLEAR_HASH).unwrap().to_string(),
+            "option name Clear Hash type button"
+        );
+        assert_eq!(
+            find(SHOW_WDL).unwrap().to_string(),
+            "option name UCI_ShowWDL type check default false"
+        );
+        assert_eq!(
+            combo.to_string(),
+            "option name Style type combo default Normal var Solid var Normal"
+        );
+        assert_eq!(string.to_string(), "option name Book File type string default <empty>");
+    }
+}
@@ -1,38 +1,90 @@
 use super::r#move::UciMove;
-use crate::search::report::{Report, Reporter};
-use std::cell::Cell;
+use super::options::{self, Options};
+use crate::eval::wdl::{self, Wdl};
+use crate::movegen::Move;
+use crate::position::Position;
+use crate::search::{
+    report::{Report, Reporter},
+    tree::{self, SearchTree},
+    tt::Bound,
+};
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Duration;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct UciReporter {
     best_move: Cell<Option<UciMove>>,
+    candidates: RefCell<Vec<(Move, i32)>>,
+    show_wdl: bool,
+    debug: bool,
+    currmove_delay: Duration,
+    last_sent_at: Cell<Duration>,
+    tree_file: String,
+    material: u32,
 }
 
 impl UciReporter {
-    pub fn new() -> Self {
+    pub fn new(pos: &Position, options: &Options, debug: bool) -> Self {
         Self {
             best_move: Cell::new(None),
+            candidates: RefCell::new(vec![]),
+            show_wdl: options.check(options::SHOW_WDL),
+            debug,
+            currmove_delay: Duration::from_millis(options.spin(options::CURRMOVE_DELAY) as u64),
+            last_sent_at: Cell::new(Duration::ZERO),
+            tree_file: options.string(options::TREE_FILE).to_string(),
+            material: wdl::material(&pos.board),
         }
     }
 
     pub fn best_move(&self) -> Option<UciMove> {
         self.best_move.get()
     }
+
+    pub fn candidates(&self) -> Vec<(Move, i32)> {
+        self.candidates.borrow().clone()
+    }
+
+    fn progress_info(&self, report: &Report) -> Vec<String> {
+        let elapsed = report.elapsed();
+        self.last_sent_at.set(elapsed);
+
+        vec![
+            format!("nodes {}", report.nodes),
+            format!("nps {}", report.nodes * 1000 / elapsed.as_millis().max(1)),
+            format!("hashfull {}", report.tt_usage),
+            format!("time {}", elapsed.as_millis()),
+        ]
+    }
 }
 
 impl Reporter for UciReporter {
     fn send(&self, report: &Report) {
         let mut info = vec![
             format!("depth {}", report.depth),
-            format!("nodes {}", report.nodes),
-            format!("nps {}", report.nodes * 1000 / report.elapsed().as_millis().max(1)),
-            format!("hashfull {}", report.tt_usage),
-            format!("time {}", report.elapsed().as_millis()),
+            format!("seldepth {}", report.seldepth),
         ];
+        info.extend(self.progress_info(report));
 
         if let Some((moves, eval)) = &report.pv {
             if let Some(plies) = report.moves_until_mate() {
                 info.push(format!("score mate {}", plies.div_ceil(2) as i32 * eval.signum()));
             } else {
-                info.push(format!("score cp {}", eval));
+                info.push(format!("score cp {eval}"));
+            }
+
+            match report.bound {
+                Bound::Lower => info.push("lowerbound".to_string()),
+                Bound::Upper => info.push("upperbound".to_string()),
+                Bound::Exact => (),
+            }
+
+            if self.show_wdl {
+                let Wdl { win, draw, loss } = wdl::wdl(*eval, self.material);
+                info.push(format!("wdl {win} {draw} {loss}"));
             }
 
             if !moves.is_empty() {
@@ -45,10 +97,57 @@ impl Reporter for UciReporter {
                         .join(" ")
                 ));
 
-                self.best_move.set(Some(moves[0].into()));
+                // Bounded scores come from unfinished re-searches, so keep the
+                // best move from the last completed iteration.
+                if let Bound::Exact = report.bound {
+                    self.best_move.set(Some(moves[0].into()));
+                    *self.candidates.borrow_mut() = report.candidates.clone();
+                }
             }
         }
 
         println!("info {}", info.join(" "));
+
+        // Search statistics help judge the effect of search changes.
+        if self.debug {
+            for line in report.stats.summary(report.nodes as u64) {
+                println!("info string {line}");
+            }
+        }
+    }
+
+    // Only shown once the search has been running for a while, otherwise
+    // the output would be flooded at low depths.
+    fn send_current_move(&self, report: &Report, mv: Move, move_number: u8) {
+        if report.elapsed() >= self.currmove_delay {
+            println!(
+                "info depth {} currmove {} currmovenumber {move_number}",
+                report.depth + 1,
+                UciMove::from(mv)
+            );
+        }
+    }
+
+    fn send_progress(&self, report: &Report) {
+        if report.elapsed() >= self.last_sent_at.get() + PROGRESS_INTERVAL {
+            println!("info {}", self.progress_info(report).join(" "));
+        }
+    }
+
+    fn send_tree(&self, tree: &SearchTree) {
+        let written = File::create(&self.tree_file).and_then(|file| tree.write(&mut BufWriter::new(file)));
+
+        match written {
+            Ok(()) => println!(
+                "info string search tree of {} nodes written to {}",
+                tree.nodes.len(),
+                self.tree_file
+            ),
+            Err(err) => println!("info string could not write search tree: {err}"),
+        }
+
+        if tree.is_truncated {
+            println!("info string search tree truncated at {} nodes", tree::MAX_NODES);
+        }
     }
 }
@@ -0,0 +1,32 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Runs the engine's `perftsuite` command over tests/data/perftsuite.epd, or
+// the suite at $PERFTSUITE, optionally limited to $PERFTSUITE_DEPTH. Positions
+// the engine can't load yet, such as Chess960 ones, and those without counts
+// within the max depth are reported as skipped and don't fail the test, but
+// at least one position must be checked.
+#[test]
+#[ignore]
+fn perft_suite() {
+    let path = std::env::var("PERFTSUITE")
+        .unwrap_or_else(|_| concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/perftsuite.epd").to_string());
+    let max_depth = std::env::var("PERFTSUITE_DEPTH").unwrap_or_default();
+
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_anodos"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = engine.stdin.take().unwrap();
+    writeln!(stdin, "perftsuite {path} {max_depth}").unwrap();
+    writeln!(stdin, "quit").unwrap();
+    drop(stdin);
+
+    let output = String::from_utf8(engine.wait_with_output().unwrap().stdout).unwrap();
+
+    assert!(output.contains("\npassed: "), "{output}");
+    assert!(!output.contains("\npassed: 0\n"), "{output}");
+    assert!(output.contains("\nfailed: 0\n"), "{output}");
+}